@@ -0,0 +1,813 @@
+//! Reading events from iCalendar (.ics) files, for example as synced by
+//! vdirsyncer. Only what is needed to know when the user is busy: the
+//! start, end and summary of events. Recurring events are expanded when
+//! asking for the events in a period.
+
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use color_eyre::eyre::{bail, eyre, Context};
+use jiff::civil::{Date, DateTime, Weekday};
+use jiff::tz::TimeZone;
+use jiff::{Span, Zoned};
+
+use crate::config::{expand_home, modified};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarEvent {
+    pub summary: String,
+    pub start: Zoned,
+    /// exclusive
+    pub end: Zoned,
+}
+
+impl CalendarEvent {
+    pub fn contains(&self, at: &Zoned) -> bool {
+        self.start <= *at && *at < self.end
+    }
+}
+
+/// The events of one or more calendars
+#[derive(Debug, Clone, Default)]
+pub struct Calendar {
+    events: Vec<Event>,
+}
+
+/// An event as written in the file, a recurring one stands for many
+#[derive(Debug, Clone)]
+struct Event {
+    first: CalendarEvent,
+    uid: Option<String>,
+    recurrence: Option<Recurrence>,
+    /// occurrences of the recurrence left out (EXDATE)
+    excluded: Vec<Zoned>,
+    /// replaces the occurrence starting at this time of the recurring
+    /// event with the same uid
+    recurrence_id: Option<Zoned>,
+    /// a cancelled event only removes the occurrence it replaces
+    cancelled: bool,
+}
+
+/// The subset of RRULE needed for the usual meetings: every other week
+/// on monday and thursday, the last friday of the month etc.
+#[derive(Debug, Clone)]
+struct Recurrence {
+    frequency: Frequency,
+    interval: i64,
+    count: Option<usize>,
+    until: Option<Zoned>,
+    /// a weekday with optionally which one in the month, -1 is the last
+    by_day: Vec<(Option<i8>, Weekday)>,
+    by_month_day: Vec<i8>,
+    by_month: Vec<i8>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl From<Vec<CalendarEvent>> for Calendar {
+    fn from(events: Vec<CalendarEvent>) -> Self {
+        Self {
+            events: events
+                .into_iter()
+                .map(|first| Event {
+                    first,
+                    uid: None,
+                    recurrence: None,
+                    excluded: Vec::new(),
+                    recurrence_id: None,
+                    cancelled: false,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Calendar {
+    pub fn extend(&mut self, other: Calendar) {
+        self.events.extend(other.events);
+    }
+
+    /// Events overlapping `from..to` sorted by start, recurring events
+    /// are expanded.
+    pub fn events_between(
+        &self,
+        from: &Zoned,
+        to: &Zoned,
+    ) -> Vec<CalendarEvent> {
+        let replaced: Vec<_> = self
+            .events
+            .iter()
+            .filter_map(|event| {
+                Some((event.uid.as_deref()?, event.recurrence_id.as_ref()?))
+            })
+            .collect();
+
+        let mut res: Vec<_> = self
+            .events
+            .iter()
+            .filter(|event| !event.cancelled)
+            .flat_map(|event| {
+                let occurrences = match &event.recurrence {
+                    Some(recurrence) => recurrence.expand(event, to),
+                    None => vec![event.first.clone()],
+                };
+                occurrences.into_iter().filter(|occurrence| {
+                    event.recurrence.is_none()
+                        || !replaced.iter().any(|(uid, at)| {
+                            event.uid.as_deref() == Some(uid)
+                                && at.timestamp()
+                                    == occurrence.start.timestamp()
+                        })
+                })
+            })
+            .filter(|event| event.start < *to && event.end > *from)
+            .collect();
+        res.sort_by(|a, b| a.start.cmp(&b.start));
+        res
+    }
+
+    pub fn busy_at(&self, at: &Zoned) -> bool {
+        let Ok(next) = at.checked_add(Duration::from_secs(1)) else {
+            return false;
+        };
+        self.events_between(at, &next)
+            .iter()
+            .any(|event| event.contains(at))
+    }
+
+    /// The parts of `from..to` taken up by events. Overlapping events
+    /// are merged.
+    pub fn busy_periods(&self, from: &Zoned, to: &Zoned) -> Vec<Range<Zoned>> {
+        let mut res: Vec<Range<Zoned>> = Vec::new();
+        for event in self.events_between(from, to) {
+            let start = event.start.max(from.clone());
+            let end = event.end.min(to.clone());
+            match res.last_mut() {
+                Some(last) if start <= last.end => {
+                    last.end = last.end.clone().max(end);
+                }
+                _ => res.push(start..end),
+            }
+        }
+        res
+    }
+}
+
+/// Reads all calendars, paths may start with `~`
+pub fn load_all(paths: &[PathBuf]) -> color_eyre::Result<Calendar> {
+    let mut calendar = Calendar::default();
+    for path in paths {
+        calendar.extend(load(&expand_home(path))?);
+    }
+    Ok(calendar)
+}
+
+/// Reads an .ics file or all .ics files in a directory and its
+/// subdirectories, as vdirsyncer stores one event per file. Files in a
+/// directory that can not be parsed are skipped.
+pub fn load(path: &Path) -> color_eyre::Result<Calendar> {
+    if !path.is_dir() {
+        return load_file(path);
+    }
+
+    let mut calendar = Calendar::default();
+    for file in ics_files(path)? {
+        match load_file(&file) {
+            Ok(events) => calendar.extend(events),
+            Err(e) => eprintln!("Skipping calendar file: {e:?}"),
+        }
+    }
+    Ok(calendar)
+}
+
+fn load_file(path: &Path) -> color_eyre::Result<Calendar> {
+    let content = fs::read_to_string(path).wrap_err_with(|| {
+        format!("Could not read calendar at: {}", path.display())
+    })?;
+    parse(&content).wrap_err_with(|| {
+        format!("Could not parse calendar at: {}", path.display())
+    })
+}
+
+/// The .ics files in a directory and its subdirectories, sorted
+fn ics_files(dir: &Path) -> color_eyre::Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(dir)
+        .wrap_err_with(|| {
+            format!("Could not read calendar dir at: {}", dir.display())
+        })?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .wrap_err("Could not list calendar dir")?;
+    entries.sort();
+
+    let mut res = Vec::new();
+    for entry in entries {
+        if entry.is_dir() {
+            match ics_files(&entry) {
+                Ok(files) => res.extend(files),
+                Err(e) => eprintln!("Skipping calendar dir: {e:?}"),
+            }
+        } else if entry.extension().and_then(|ext| ext.to_str()) == Some("ics")
+        {
+            res.push(entry);
+        }
+    }
+    Ok(res)
+}
+
+/// Keeps the calendars read before, a file is only read again once it
+/// changed. A file that can not be read is reported once, its previous
+/// events are kept until the file changes again.
+#[derive(Default)]
+pub struct CalendarWatcher {
+    paths: Vec<PathBuf>,
+    files: HashMap<PathBuf, WatchedFile>,
+}
+
+struct WatchedFile {
+    modified: Option<SystemTime>,
+    calendar: Calendar,
+}
+
+impl CalendarWatcher {
+    /// Paths may start with `~`, they are read on the first call to
+    /// [`CalendarWatcher::calendar`]
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        Self {
+            paths,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Files no longer part of the calendars are forgotten on the next
+    /// call to [`CalendarWatcher::calendar`]
+    pub fn set_paths(&mut self, paths: Vec<PathBuf>) {
+        self.paths = paths;
+    }
+
+    /// The events of all calendars
+    pub fn calendar(&mut self) -> Calendar {
+        let mut current = Vec::new();
+        for path in &self.paths {
+            let path = expand_home(path);
+            if !path.is_dir() {
+                current.push(path);
+                continue;
+            }
+            match ics_files(&path) {
+                Ok(files) => current.extend(files),
+                Err(e) => eprintln!("Skipping calendar dir: {e:?}"),
+            }
+        }
+        self.files.retain(|path, _| current.contains(path));
+
+        let mut calendar = Calendar::default();
+        for path in current {
+            let modified = modified(&path);
+            let changed = self
+                .files
+                .get(&path)
+                .is_none_or(|watched| watched.modified != modified);
+            if changed {
+                let read =
+                    load_file(&path).inspect_err(|e| eprintln!("{e:?}")).ok();
+                let watched =
+                    self.files.entry(path.clone()).or_insert(WatchedFile {
+                        modified,
+                        calendar: Calendar::default(),
+                    });
+                watched.modified = modified;
+                if let Some(read) = read {
+                    watched.calendar = read;
+                }
+            }
+            calendar.extend(self.files[&path].calendar.clone());
+        }
+        calendar
+    }
+}
+
+/// A property with its parameters, `DTSTART;TZID=Europe/Paris:2026...`
+struct Property<'a> {
+    name: &'a str,
+    params: Vec<(&'a str, &'a str)>,
+    value: &'a str,
+}
+
+impl Property<'_> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| *value)
+    }
+}
+
+/// An event that can not be understood, for example as it uses a rule
+/// or time zone we do not support, is skipped. Only if no event in the
+/// file can be read that is an error.
+pub fn parse(ics: &str) -> color_eyre::Result<Calendar> {
+    let lines = unfold(ics);
+    let mut events = Vec::new();
+    let mut invalid: Vec<color_eyre::Report> = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+    for (idx, line) in lines.iter().enumerate() {
+        let property = parse_line(line)
+            .ok_or_else(|| eyre!("Invalid line {}: {line}", idx + 1))?;
+        match (property.name, property.value) {
+            ("BEGIN", "VEVENT") => current = Some(Vec::new()),
+            ("END", "VEVENT") => {
+                let properties = current
+                    .take()
+                    .ok_or_else(|| eyre!("END:VEVENT without BEGIN"))?;
+                match event(&properties).wrap_err_with(|| {
+                    format!("Invalid event ending on line {}", idx + 1)
+                }) {
+                    Ok(event) => events.extend(event),
+                    Err(e) => invalid.push(e),
+                }
+            }
+            _ => {
+                if let Some(properties) = &mut current {
+                    properties.push(property);
+                }
+            }
+        }
+    }
+
+    if events.is_empty() && !invalid.is_empty() {
+        return Err(invalid.swap_remove(0));
+    }
+    for e in invalid {
+        eprintln!("Skipping calendar event: {e:?}");
+    }
+    Ok(Calendar { events })
+}
+
+/// Long lines are split by starting the next line with whitespace
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ if line.trim().is_empty() => (),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+    lines
+}
+
+fn parse_line(line: &str) -> Option<Property<'_>> {
+    // parameter values may contain a colon if they are quoted
+    let mut quoted = false;
+    let split = line.char_indices().find_map(|(idx, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(idx),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..split], &line[split + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?;
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(name, value)| (name, value.trim_matches('"')))
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value,
+    })
+}
+
+/// None for events that do not block time (TRANSP:TRANSPARENT)
+fn event(properties: &[Property]) -> color_eyre::Result<Option<Event>> {
+    let find = |name: &str| {
+        properties
+            .iter()
+            .find(|property| property.name.eq_ignore_ascii_case(name))
+    };
+    let value = |name: &str| find(name).map(|property| property.value);
+    if value("TRANSP") == Some("TRANSPARENT") {
+        return Ok(None);
+    }
+
+    let summary = value("SUMMARY").map(unescape).unwrap_or_default();
+    let start = find("DTSTART").ok_or_else(|| eyre!("Event has no start"))?;
+    let (start, all_day) = time(start)?;
+
+    let end = if let Some(end) = find("DTEND") {
+        time(end)?.0
+    } else if let Some(duration) = value("DURATION") {
+        let span: Span =
+            duration.parse().wrap_err("Could not parse duration")?;
+        start
+            .checked_add(span)
+            .wrap_err("Event ends too far in the future")?
+    } else if all_day {
+        start
+            .tomorrow()
+            .wrap_err("Event ends too far in the future")?
+    } else {
+        start.clone()
+    };
+
+    let recurrence = value("RRULE")
+        .map(|rule| {
+            Recurrence::parse(rule, start.time_zone())
+                .wrap_err_with(|| format!("Invalid recurrence rule: {rule}"))
+        })
+        .transpose()?;
+    let mut excluded = Vec::new();
+    for property in properties
+        .iter()
+        .filter(|property| property.name.eq_ignore_ascii_case("EXDATE"))
+    {
+        for value in property.value.split(',') {
+            let property = Property {
+                value,
+                name: property.name,
+                params: property.params.clone(),
+            };
+            excluded.push(time(&property)?.0);
+        }
+    }
+    let recurrence_id = find("RECURRENCE-ID")
+        .map(|property| time(property).map(|(at, _)| at))
+        .transpose()?;
+
+    Ok(Some(Event {
+        first: CalendarEvent {
+            summary,
+            start,
+            end,
+        },
+        uid: value("UID").map(str::to_owned),
+        recurrence,
+        excluded,
+        recurrence_id,
+        cancelled: value("STATUS") == Some("CANCELLED"),
+    }))
+}
+
+/// Also returns whether the value is a date without a time
+fn time(property: &Property) -> color_eyre::Result<(Zoned, bool)> {
+    let tz = match property.param("TZID") {
+        Some(id) => TimeZone::get(id)
+            .wrap_err_with(|| format!("Unknown time zone: {id}"))?,
+        None => TimeZone::system(),
+    };
+    let is_date = property.param("VALUE") == Some("DATE");
+    time_in(property.value, tz, is_date)
+}
+
+fn time_in(
+    value: &str,
+    tz: TimeZone,
+    is_date: bool,
+) -> color_eyre::Result<(Zoned, bool)> {
+    if is_date || value.len() == 8 {
+        let date = Date::strptime("%Y%m%d", value)
+            .wrap_err_with(|| format!("Invalid date: {value}"))?;
+        let start = date
+            .to_zoned(tz)
+            .wrap_err("Day does not exist in time zone")?;
+        return Ok((start, true));
+    }
+
+    let (value, tz) = match value.strip_suffix('Z') {
+        Some(value) => (value, TimeZone::UTC),
+        None => (value, tz),
+    };
+    let time = DateTime::strptime("%Y%m%dT%H%M%S", value)
+        .wrap_err_with(|| format!("Invalid date and time: {value}"))?;
+    let time = time
+        .to_zoned(tz)
+        .wrap_err("Time does not exist in time zone")?;
+    Ok((time, false))
+}
+
+impl Recurrence {
+    /// `tz` is that of the start, UNTIL without Z is in it
+    fn parse(rule: &str, tz: &TimeZone) -> color_eyre::Result<Self> {
+        let mut frequency = None;
+        let mut recurrence = Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+        };
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| eyre!("Missing = in: {part}"))?;
+            let number = |value: &str| {
+                value
+                    .parse::<i8>()
+                    .wrap_err_with(|| format!("Invalid number: {value}"))
+            };
+            match name {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        other => bail!("Unsupported frequency: {other}"),
+                    })
+                }
+                "INTERVAL" => {
+                    recurrence.interval = value
+                        .parse::<i64>()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(|| eyre!("Invalid interval: {value}"))?;
+                }
+                "COUNT" => {
+                    recurrence.count =
+                        Some(value.parse().wrap_err_with(|| {
+                            format!("Invalid count: {value}")
+                        })?);
+                }
+                "UNTIL" => {
+                    recurrence.until =
+                        Some(time_in(value, tz.clone(), false)?.0);
+                }
+                "BYDAY" => {
+                    recurrence.by_day = value
+                        .split(',')
+                        .map(by_day)
+                        .collect::<color_eyre::Result<_>>()?;
+                }
+                "BYMONTHDAY" => {
+                    recurrence.by_month_day = value
+                        .split(',')
+                        .map(number)
+                        .collect::<color_eyre::Result<_>>(
+                    )?;
+                }
+                "BYMONTH" => {
+                    recurrence.by_month = value
+                        .split(',')
+                        .map(number)
+                        .collect::<color_eyre::Result<_>>()?;
+                }
+                // only matters for rules we do not support
+                "WKST" => (),
+                _ => bail!("Unsupported part: {part}"),
+            }
+        }
+        recurrence.frequency =
+            frequency.ok_or_else(|| eyre!("Rule has no frequency"))?;
+        if matches!(recurrence.frequency, Frequency::Weekly)
+            && !recurrence.by_month_day.is_empty()
+        {
+            bail!("BYMONTHDAY can not be used with FREQ=WEEKLY");
+        }
+        Ok(recurrence)
+    }
+
+    /// The occurrences of `event` that start before `to`
+    fn expand(&self, event: &Event, to: &Zoned) -> Vec<CalendarEvent> {
+        let first = &event.first;
+        let tz = first.start.time_zone().clone();
+        let Ok(length) = first
+            .start
+            .datetime()
+            .until(first.end.with_time_zone(tz.clone()).datetime())
+        else {
+            return Vec::new();
+        };
+        let last_date = to.with_time_zone(tz.clone()).date();
+
+        let mut res = Vec::new();
+        let mut seen = 0;
+        for period in 0.. {
+            let Some((period_start, dates)) =
+                self.dates(first.start.date(), period)
+            else {
+                break;
+            };
+            if period_start > last_date {
+                break;
+            }
+            for date in dates {
+                if date < first.start.date()
+                    || !(self.by_month.is_empty()
+                        || self.by_month.contains(&date.month()))
+                {
+                    continue;
+                }
+                let Ok(start) =
+                    date.to_datetime(first.start.time()).to_zoned(tz.clone())
+                else {
+                    continue;
+                };
+                if self.until.as_ref().is_some_and(|until| start > *until)
+                    || self.count.is_some_and(|count| seen >= count)
+                    || start >= *to
+                {
+                    return res;
+                }
+                seen += 1;
+                if event
+                    .excluded
+                    .iter()
+                    .any(|at| at.timestamp() == start.timestamp())
+                {
+                    continue;
+                }
+                let Ok(end) = start.checked_add(length) else {
+                    continue;
+                };
+                res.push(CalendarEvent {
+                    summary: first.summary.clone(),
+                    start,
+                    end,
+                });
+            }
+        }
+        res
+    }
+
+    /// The start of the `period`th period after the one containing
+    /// `first` and the sorted days in it matching the rule. None if
+    /// that is too far in the future.
+    fn dates(&self, first: Date, period: i64) -> Option<(Date, Vec<Date>)> {
+        let steps = period.checked_mul(self.interval)?;
+        let weekday_matches = |date: &Date| {
+            self.by_day.iter().any(|(_, day)| *day == date.weekday())
+        };
+        let (start, mut dates) = match self.frequency {
+            Frequency::Daily => {
+                let day = first
+                    .checked_add(Span::new().try_days(steps).ok()?)
+                    .ok()?;
+                let matches = (self.by_day.is_empty() || weekday_matches(&day))
+                    && (self.by_month_day.is_empty()
+                        || self
+                            .by_month_day
+                            .iter()
+                            .any(|month_day| is_month_day(day, *month_day)));
+                (day, if matches { vec![day] } else { Vec::new() })
+            }
+            Frequency::Weekly => {
+                let day = first
+                    .checked_add(Span::new().try_weeks(steps).ok()?)
+                    .ok()?;
+                let offset = i64::from(day.weekday().to_monday_zero_offset());
+                let monday = day.checked_sub(Span::new().days(offset)).ok()?;
+                let dates = if self.by_day.is_empty() {
+                    vec![day]
+                } else {
+                    self.by_day
+                        .iter()
+                        .filter_map(|(_, weekday)| {
+                            let offset = weekday.to_monday_zero_offset();
+                            monday.checked_add(Span::new().days(offset)).ok()
+                        })
+                        .collect()
+                };
+                (monday, dates)
+            }
+            Frequency::Monthly => {
+                let month = first
+                    .first_of_month()
+                    .checked_add(Span::new().try_months(steps).ok()?)
+                    .ok()?;
+                (month, self.days_in_month(month, first.day()))
+            }
+            Frequency::Yearly => {
+                let year = first
+                    .first_of_year()
+                    .checked_add(Span::new().try_years(steps).ok()?)
+                    .ok()?;
+                let months = if self.by_month.is_empty() {
+                    vec![first.month()]
+                } else {
+                    self.by_month.clone()
+                };
+                let dates = months
+                    .into_iter()
+                    .filter_map(|month| Date::new(year.year(), month, 1).ok())
+                    .flat_map(|month| self.days_in_month(month, first.day()))
+                    .collect();
+                (year, dates)
+            }
+        };
+        dates.sort();
+        dates.dedup();
+        Some((start, dates))
+    }
+
+    /// Days matching BYDAY and BYMONTHDAY in the month starting at
+    /// `month`, without those the same day of the month as the start
+    fn days_in_month(&self, month: Date, start_day: i8) -> Vec<Date> {
+        let month_day = |day: i8| {
+            if day > 0 {
+                Date::new(month.year(), month.month(), day).ok()
+            } else {
+                let from_end = Span::new().days(i64::from(day) + 1);
+                month.last_of_month().checked_add(from_end).ok()
+            }
+            .filter(|date| date.month() == month.month())
+        };
+        let all_days = || (1..=month.days_in_month()).filter_map(month_day);
+
+        if !self.by_day.is_empty() {
+            self.by_day
+                .iter()
+                .flat_map(|(nth, weekday)| match nth {
+                    Some(nth) => month
+                        .nth_weekday_of_month(*nth, *weekday)
+                        .ok()
+                        .into_iter()
+                        .collect(),
+                    None => all_days()
+                        .filter(|date| date.weekday() == *weekday)
+                        .collect::<Vec<_>>(),
+                })
+                .filter(|date| {
+                    self.by_month_day.is_empty()
+                        || self
+                            .by_month_day
+                            .iter()
+                            .any(|day| month_day(*day) == Some(*date))
+                })
+                .collect()
+        } else if !self.by_month_day.is_empty() {
+            self.by_month_day
+                .iter()
+                .filter_map(|day| month_day(*day))
+                .collect()
+        } else {
+            month_day(start_day).into_iter().collect()
+        }
+    }
+}
+
+/// Undoes the escaping of TEXT values: `\\`, `\;`, `\,` and `\n` or `\N`
+/// for a line break
+fn unescape(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => res.push('\n'),
+            Some(escaped) => res.push(escaped),
+            None => res.push('\\'),
+        }
+    }
+    res
+}
+
+/// Negative days count from the end of the month, -1 is the last day
+fn is_month_day(date: Date, day: i8) -> bool {
+    if day > 0 {
+        date.day() == day
+    } else {
+        date.day() - date.days_in_month() - 1 == day
+    }
+}
+
+/// `MO`, `2TU` or `-1FR`
+fn by_day(value: &str) -> color_eyre::Result<(Option<i8>, Weekday)> {
+    let split = value.len().saturating_sub(2);
+    let (nth, day) = value.split_at(split);
+    let weekday = match day {
+        "MO" => Weekday::Monday,
+        "TU" => Weekday::Tuesday,
+        "WE" => Weekday::Wednesday,
+        "TH" => Weekday::Thursday,
+        "FR" => Weekday::Friday,
+        "SA" => Weekday::Saturday,
+        "SU" => Weekday::Sunday,
+        _ => bail!("Invalid weekday: {value}"),
+    };
+    let nth = match nth.trim_start_matches('+') {
+        "" => None,
+        nth => Some(
+            nth.parse()
+                .wrap_err_with(|| format!("Invalid weekday: {value}"))?,
+        ),
+    };
+    Ok((nth, weekday))
+}
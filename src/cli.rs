@@ -1,10 +1,12 @@
-use core::ops::Range;
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::time::Duration;
 
-use clap::{Args, Parser, Subcommand};
-use itertools::Itertools;
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
 
-use crate::Activity;
+use crate::window::Window;
+use crate::{Activity, IdleAction, SkipPolicy};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -13,17 +15,82 @@ pub struct Cli {
     pub command: Command,
 }
 
+impl Cli {
+    /// Like [`Parser::parse`] but also exits on combinations of arguments
+    /// clap can not reject by itself, see [`Self::check`]
+    pub fn parse_checked() -> Self {
+        let cli = Self::parse();
+        if let Err(e) = cli.check() {
+            e.exit()
+        }
+        cli
+    }
+
+    /// `--work` and `--break` only apply to the standalone source, clap
+    /// can only require that some source is picked.
+    pub fn check(&self) -> Result<(), clap::Error> {
+        let run_args = match &self.command {
+            Command::Run(run_args) | Command::Install(run_args) => run_args,
+            Command::Test(test_args) => &test_args.run_args,
+            _ => return Ok(()),
+        };
+        let timer_set = run_args.work.is_some() || run_args.break_.is_some();
+        if timer_set && run_args.source_kind() != SourceKind::Standalone {
+            return Err(Self::command().error(
+                ErrorKind::ArgumentConflict,
+                "--work and --break can only be used with the standalone \
+                 source",
+            ));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Subcommand)]
 pub enum Command {
     Run(RunArgs),
     /// do not connect to break-enforcer but simulate a run.
     Test(TestArgs),
+    /// Install a service that starts `run` on login using the config file.
+    /// Any other arguments given are written to the config file first.
     Install(RunArgs),
     Remove,
+    /// Summarise how often activities were done, skipped or only shown
+    Stats(StatsArgs),
+    /// Write everything that happened to a file
+    Export(ExportArgs),
+    /// Print the reminders of a simulated day without opening any window.
+    /// Useful to tune counts and load before installing.
+    Simulate(SimulateArgs),
+    /// Print the reminders a recording made with `--record` leads to.
+    /// Attach the recording when reporting a bug.
+    Replay(ReplayArgs),
+    /// Show the state of the running instance
+    Status,
+    /// Tell the running instance an activity was done
+    Done {
+        /// Description of the activity as in the config
+        activity: String,
+    },
+    /// Make the running instance show its reminders now
+    Trigger,
+    /// Make the running instance stop showing reminders for a while
+    Pause(PauseArgs),
+    /// End a pause early
+    Resume,
 }
 
+/// The settings that can also be set in the config file
 #[derive(Args, Clone)]
-pub struct RunArgs {
+pub struct ConfigArgs {
+    /// Path to the config file. Defaults to:
+    /// ~/.config/break-filler/config.toml
+    ///
+    /// Arguments passed on the command line take precedence over the
+    /// values in the config file.
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+
     /// Activity to remind and frequency (multiple supported). Leaving
     /// out frequency means the activity is issued every break.
     ///
@@ -38,25 +105,84 @@ pub struct RunArgs {
     /// by the user prepend an exclamation mark (!).
     ///
     /// Example: '!eat:1'
+    ///
+    /// To only issue the activity during part of the day append an at
+    /// sign (@) followed by the window.
+    ///
+    /// Example: 'eat lunch:1@11:30..13:30'
     #[arg(short, long, value_parser = reminder_parser)]
     pub activity: Vec<Activity>,
 
     /// Start and end time in between which reminders should
-    /// be issued:
-    #[arg(short, long, value_parser = window_parser, default_value = "00:00..23:59")]
-    pub window: Range<jiff::civil::Time>,
+    /// be issued. When not set here or in the config file: 00:00..23:59
+    ///
+    /// Multiple ranges can be given separated by a comma. Breaks outside
+    /// the ranges, for example during lunch, are not counted.
+    ///
+    /// Example: 09:00..12:00,13:00..17:30
+    #[arg(short, long, value_parser = Window::from_str)]
+    pub window: Option<Window>,
 
     /// The percentage the user will be behind the computer during the window
-    /// as a number between 0 and 1.0
-    #[arg(short, long, default_value = "1.0")]
-    pub load: f32,
+    /// as a number between 0 and 1.0. When not set here or in the config
+    /// file: 1.0
+    #[arg(short, long)]
+    pub load: Option<f32>,
 
     /// If there is a visible window with this title then do not open a
     /// reminder unless this is the last possibility to issue the reminder
     ///
-    /// Note: multiple are allowed, case-insensitive
+    /// Note: multiple are allowed, case-insensitive. Rules matching on
+    /// a title regex, app id, workspace or only focused or fullscreen
+    /// windows can be set in the config file.
     #[arg(short, long)]
     pub skip_when_visible: Vec<String>,
+
+    /// An .ics file or a directory of them, for example synced by
+    /// vdirsyncer. During meetings reminders wait unless this is the last
+    /// possibility to issue them. Time in meetings is not counted as time
+    /// left for reminders.
+    ///
+    /// Note: multiple are allowed, recurring events are supported.
+    #[arg(long)]
+    pub calendar: Vec<PathBuf>,
+
+    /// Print why each activity was or was not shown at every break
+    #[arg(long)]
+    pub explain: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct RunArgs {
+    #[command(flatten)]
+    pub config_args: ConfigArgs,
+
+    /// Where break events come from. When not set: break-enforcer
+    #[arg(long, value_enum, group = "source_kind")]
+    pub source: Option<SourceKind>,
+    /// Events to replay when using `--source script`, one per line. Use
+    /// - to read them from stdin.
+    #[arg(long, required_if_eq("source", "script"))]
+    pub script: Option<PathBuf>,
+
+    /// Append every message from the break source to this file. Use
+    /// `replay` to see what reminders they lead to.
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+
+    /// Short for `--source standalone`
+    #[arg(long, group = "source_kind")]
+    pub standalone: bool,
+    /// Time between breaks for the built-in timer. Default: 00:25
+    #[arg(long, value_parser = duration_parser, requires = "source_kind")]
+    pub work: Option<Duration>,
+    /// Length of the breaks for the built-in timer. Default: 00:05
+    #[arg(
+        long = "break",
+        value_parser = duration_parser,
+        requires = "source_kind"
+    )]
+    pub break_: Option<Duration>,
 }
 
 #[derive(Args, Clone)]
@@ -75,9 +201,183 @@ pub struct TestArgs {
     pub periods: usize,
 }
 
+#[derive(Args, Clone)]
+pub struct SimulateArgs {
+    #[command(flatten)]
+    pub config_args: ConfigArgs,
+
+    /// Time between breaks
+    #[arg(
+        short = 'o',
+        long,
+        value_parser = duration_parser,
+        default_value = "00:25"
+    )]
+    pub work_duration: Duration,
+    #[arg(
+        short,
+        long,
+        value_parser = duration_parser,
+        default_value = "00:05"
+    )]
+    pub break_duration: Duration,
+
+    /// Day to simulate, matters if the config has a schedule. Defaults
+    /// to today.
+    ///
+    /// Example: 2026-10-19
+    #[arg(short, long)]
+    pub date: Option<jiff::civil::Date>,
+}
+
+#[derive(Args, Clone)]
+pub struct ReplayArgs {
+    #[command(flatten)]
+    pub config_args: ConfigArgs,
+
+    /// File written by `run --record`
+    pub recording: PathBuf,
+}
+
+#[derive(Args, Clone)]
+pub struct PauseArgs {
+    /// How long to pause. Leave out to pause until `resume` is called.
+    /// The pause survives restarts.
+    ///
+    /// Example: 2h or 1h 30m
+    #[arg(long = "for", value_parser = span_parser)]
+    pub duration: Option<Duration>,
+}
+
+impl ConfigArgs {
+    /// The config file passed as argument or otherwise the default location
+    pub fn config_path(&self) -> color_eyre::Result<PathBuf> {
+        match &self.config {
+            Some(path) => Ok(path.clone()),
+            None => crate::config::default_path(),
+        }
+    }
+
+    /// Whether any setting that can also be set in the config file was
+    /// passed on the command line
+    pub fn overrides_config(&self) -> bool {
+        !self.activity.is_empty()
+            || self.window.is_some()
+            || self.load.is_some()
+            || !self.skip_when_visible.is_empty()
+            || !self.calendar.is_empty()
+    }
+}
+
+impl RunArgs {
+    /// The arguments about where breaks come from, these are not
+    /// stored in the config file.
+    pub fn source_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.standalone {
+            args.push("--standalone".to_owned());
+        }
+        if let Some(source) = self.source {
+            let name =
+                source.to_possible_value().expect("no variant is skipped");
+            args.push("--source".to_owned());
+            args.push(name.get_name().to_owned());
+        }
+        for (flag, path) in
+            [("--script", &self.script), ("--record", &self.record)]
+        {
+            if let Some(path) = path {
+                args.push(flag.to_owned());
+                args.push(path.display().to_string());
+            }
+        }
+        for (flag, duration) in
+            [("--work", self.work), ("--break", self.break_)]
+        {
+            if let Some(duration) = duration {
+                let minutes = duration.as_secs() / 60;
+                args.push(flag.to_owned());
+                args.push(format!("{:02}:{:02}", minutes / 60, minutes % 60));
+            }
+        }
+        args
+    }
+
+    /// The source selected by `--source` or `--standalone`
+    pub fn source_kind(&self) -> SourceKind {
+        match self.source {
+            Some(kind) => kind,
+            None if self.standalone => SourceKind::Standalone,
+            None => SourceKind::BreakEnforcer,
+        }
+    }
+}
+
+#[derive(Args, Clone)]
+pub struct StatsArgs {
+    /// Only include what happened on or after this date.
+    ///
+    /// Example: 2026-10-01
+    #[arg(short, long)]
+    pub since: Option<jiff::civil::Date>,
+
+    /// Only include activities containing this, ignoring case
+    #[arg(short, long)]
+    pub activity: Option<String>,
+
+    /// Print json instead of a table
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct ExportArgs {
+    #[arg(short, long)]
+    pub format: ExportFormat,
+
+    /// File to write to, leave out to print the export
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Only include what happened on or after this date.
+    ///
+    /// Example: 2026-10-01
+    #[arg(short, long)]
+    pub since: Option<jiff::civil::Date>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceKind {
+    /// Listen to the break-enforcer daemon
+    BreakEnforcer,
+    /// Time the breaks with a built-in timer. If swayidle is installed
+    /// being away for longer than a break restarts the work period.
+    Standalone,
+    /// Read the events from a file, see `--script`
+    Script,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum ExportFormat {
+    /// Every event on a row
+    Csv,
+    /// Every event as a json object on its own line
+    Jsonl,
+    /// iCalendar with an event for every activity done
+    Ics,
+}
+
 fn reminder_parser(s: &str) -> Result<Activity, String> {
     let needs_confirm = s.chars().next().is_some_and(|c| c == '!');
     let s = s.trim_start_matches('!');
+    // an at sign not followed by a window is part of the description
+    let (s, window) = match s
+        .rsplit_once('@')
+        .and_then(|(rest, window)| Some((rest, window.parse().ok()?)))
+    {
+        Some((s, window)) => (s, Some(window)),
+        None => (s, None),
+    };
 
     if s.chars().filter(|c| *c == ':').count() > 1 {
         return Err(
@@ -92,47 +392,22 @@ fn reminder_parser(s: &str) -> Result<Activity, String> {
                 .parse()
                 .map_err(|e| format!("Could not parse count as number: {e}"))?,
             needs_confirm,
+            window,
+            idle: IdleAction::default(),
+            skip: SkipPolicy::default(),
         })
     } else {
         Ok(Activity {
             description: s.to_owned(),
             count: usize::MAX,
             needs_confirm,
+            window,
+            idle: IdleAction::default(),
+            skip: SkipPolicy::default(),
         })
     }
 }
 
-fn window_parser(s: &str) -> Result<Range<jiff::civil::Time>, String> {
-    let range_tokens = s
-        .chars()
-        .tuple_windows()
-        .filter(|(c1, c2)| *c1 == '.' && *c2 == '.')
-        .count();
-    if range_tokens != 1 {
-        return Err("Range must contain exactly one occurence of ..".to_owned());
-    }
-
-    if let Some((start, end)) = s.split_once("..") {
-        let start =
-            jiff::civil::Time::strptime("%H:%M", start).map_err(|e| {
-                format!(
-                    "Could not parse start time, should be \
-                in format: 12:34 (hh:mm). Parse error: {e}"
-                )
-            })?;
-        let end = jiff::civil::Time::strptime("%H:%M", end).map_err(|e| {
-            format!(
-                "Could not parse end time, should be \
-                in format: 12:34 (hh:mm). Parse error: {e}"
-            )
-        })?;
-
-        Ok(start..end)
-    } else {
-        unreachable!("We checked that '..' occured once")
-    }
-}
-
 fn duration_parser(s: &str) -> Result<Duration, String> {
     jiff::civil::Time::strptime("%H:%M", s)
         .map_err(|e| {
@@ -145,6 +420,14 @@ fn duration_parser(s: &str) -> Result<Duration, String> {
         .map(Duration::from_secs)
 }
 
+fn span_parser(s: &str) -> Result<Duration, String> {
+    let duration: jiff::SignedDuration = s.parse().map_err(|e| {
+        format!("Could not parse duration, should look like: 1h 30m. {e}")
+    })?;
+    Duration::try_from(duration)
+        .map_err(|_| "Duration can not be negative".to_owned())
+}
+
 fn time_parser(s: &str) -> Result<jiff::civil::Time, String> {
     jiff::civil::Time::strptime("%H:%M", s).map_err(|e| {
         format!(
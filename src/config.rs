@@ -0,0 +1,208 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use color_eyre::eyre::{Context, OptionExt};
+use jiff::civil::{self, Time};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::ConfigArgs;
+use crate::schedule::Schedule;
+use crate::skip::SkipCondition;
+use crate::window::Window;
+use crate::window_manager::WindowRule;
+use crate::Activity;
+
+/// Settings for the planner and ui. Made by combining the config file with
+/// the command line arguments. Arguments take precedence.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub activities: Vec<Activity>,
+    pub window: Window,
+    pub load: f32,
+    pub skip_when_visible: Vec<WindowRule>,
+    /// can only be set in the config file
+    pub skip_when: Option<SkipCondition>,
+    /// .ics files or directories with meetings
+    pub calendars: Vec<PathBuf>,
+    pub schedule: Schedule,
+    /// can only be set on the command line
+    pub explain: bool,
+}
+
+/// The config file as stored on disk, everything is optional.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ConfigFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<Window>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load: Option<f32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip_when_visible: Vec<WindowRule>,
+    /// Combined with skip-when-visible, reminders wait if either holds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_when: Option<SkipCondition>,
+    /// No reminders during meetings in these .ics files or directories
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calendars: Vec<PathBuf>,
+    #[serde(default, rename = "activity")]
+    pub activities: Vec<Activity>,
+    /// can only be set in the config file
+    #[serde(default, skip_serializing_if = "Schedule::is_empty")]
+    pub schedule: Schedule,
+}
+
+pub fn default_path() -> color_eyre::Result<PathBuf> {
+    #[expect(
+        deprecated,
+        reason = "windows only issue fixed in next rust version"
+    )]
+    let path = std::env::home_dir()
+        .ok_or_eyre("Could not find home dir")?
+        .join(".config")
+        .join(env!("CARGO_PKG_NAME"))
+        .join("config.toml");
+    Ok(path)
+}
+
+/// Paths in the config file may start with `~`
+pub(crate) fn expand_home(path: &Path) -> PathBuf {
+    #[expect(
+        deprecated,
+        reason = "windows only issue fixed in next rust version"
+    )]
+    let home = std::env::home_dir();
+    match (path.strip_prefix("~"), home) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_owned(),
+    }
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> color_eyre::Result<Self> {
+        let content = fs::read_to_string(path).wrap_err_with(|| {
+            format!("Could not read config file at: {}", path.display())
+        })?;
+        // the toml error includes the line and column of the problem
+        toml::from_str(&content).wrap_err_with(|| {
+            format!("Could not parse config file at: {}", path.display())
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> color_eyre::Result<()> {
+        let content =
+            toml::to_string(self).wrap_err("Could not serialize config")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .wrap_err("Could not create directory for config file")?;
+        }
+        fs::write(path, content).wrap_err_with(|| {
+            format!("Could not write config file to: {}", path.display())
+        })
+    }
+}
+
+impl Config {
+    /// Reads the config file, if the path was not explicitly passed it is
+    /// fine for the file not to exist.
+    pub fn resolve(args: &ConfigArgs) -> color_eyre::Result<Self> {
+        let path = args.config_path()?;
+        let file = if args.config.is_some() || path.exists() {
+            ConfigFile::load(&path)?
+        } else {
+            ConfigFile::default()
+        };
+        Ok(Self::merge(args, file))
+    }
+
+    fn merge(args: &ConfigArgs, file: ConfigFile) -> Self {
+        Self {
+            activities: if args.activity.is_empty() {
+                file.activities
+            } else {
+                args.activity.clone()
+            },
+            window: args.window.clone().or(file.window).unwrap_or_else(|| {
+                Window::from(Time::midnight()..civil::time(23, 59, 0, 0))
+            }),
+            load: args.load.or(file.load).unwrap_or(1.0),
+            skip_when_visible: if args.skip_when_visible.is_empty() {
+                file.skip_when_visible
+            } else {
+                args.skip_when_visible
+                    .iter()
+                    .cloned()
+                    .map(WindowRule::Title)
+                    .collect()
+            },
+            skip_when: file.skip_when,
+            calendars: if args.calendar.is_empty() {
+                file.calendars
+            } else {
+                args.calendar.clone()
+            },
+            schedule: file.schedule,
+            explain: args.explain,
+        }
+    }
+}
+
+/// Keeps track of the config file such that changes can be applied
+/// without restarting.
+pub struct ConfigWatcher {
+    args: ConfigArgs,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    pub fn load(args: ConfigArgs) -> color_eyre::Result<(Config, Self)> {
+        let path = args.config_path()?;
+        let modified = modified(&path);
+        let config = Config::resolve(&args)?;
+        Ok((
+            config,
+            Self {
+                args,
+                path,
+                modified,
+            },
+        ))
+    }
+
+    /// Returns a new config if the file changed since it was last read.
+    /// An invalid file is only reported once, the next change to it is
+    /// read again.
+    pub fn reload_if_changed(&mut self) -> color_eyre::Result<Option<Config>> {
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return Ok(None);
+        }
+
+        self.modified = modified;
+        Config::resolve(&self.args)
+            .map(Some)
+            .wrap_err("Could not reload config, keeping the previous one")
+    }
+}
+
+pub(crate) fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+impl From<Config> for ConfigFile {
+    fn from(config: Config) -> Self {
+        Self {
+            window: Some(config.window),
+            load: Some(config.load),
+            skip_when_visible: config.skip_when_visible,
+            skip_when: config.skip_when,
+            calendars: config.calendars,
+            activities: config.activities,
+            schedule: config.schedule,
+        }
+    }
+}
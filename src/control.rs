@@ -0,0 +1,238 @@
+//! Talking to the running instance over a unix socket. Every connection
+//! carries a single request and its response, each a line of json.
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::{mpsc as std_mpsc, Arc, Mutex};
+use std::time::Duration;
+use std::{env, fmt, fs, io, thread};
+
+use color_eyre::eyre::{eyre, Context};
+use color_eyre::Section;
+use iced::futures::channel::mpsc;
+use jiff::Zoned;
+use serde::{Deserialize, Serialize};
+
+use crate::history::Event;
+use crate::window::Window;
+use crate::{Message, Pause};
+
+/// Lives in `$XDG_RUNTIME_DIR`, the temp dir if that is not set
+pub fn socket_path() -> PathBuf {
+    env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(env::temp_dir)
+        .join(concat!(env!("CARGO_PKG_NAME"), ".sock"))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Request {
+    Status,
+    /// Count an activity as done without it being shown
+    Done {
+        activity: String,
+    },
+    /// Show the reminders now instead of waiting for a break
+    Trigger,
+    /// No reminders for this long, or until resumed if None
+    Pause {
+        duration: Option<Duration>,
+    },
+    Resume,
+    /// The history since the start of a day, or all of it if None
+    History {
+        since: Option<jiff::civil::Date>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Response {
+    Status(Status),
+    History(Vec<Event>),
+    /// Request handled, with a message for the user
+    Ok(String),
+    Error(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Status {
+    /// None if the schedule has no reminders today
+    pub window: Option<Window>,
+    /// Work plus break duration, None until break-enforcer sent it
+    pub period: Option<Duration>,
+    pub pause: Option<Pause>,
+    pub activities: Vec<ActivityStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityStatus {
+    pub activity: String,
+    pub done: usize,
+    /// None for activities issued every break
+    pub remaining: Option<usize>,
+    pub next_reminder: Option<Zoned>,
+}
+
+/// Carries the response from the ui back to the connection. The message
+/// it is part of has to be Clone, only the first response is sent.
+#[derive(Clone)]
+pub struct Reply(Arc<Mutex<Option<std_mpsc::Sender<Response>>>>);
+
+impl Reply {
+    pub fn send(&self, response: Response) {
+        let tx = self.0.lock().expect("nothing should panic").take();
+        if let Some(tx) = tx {
+            // the connection might have timed out
+            let _ = tx.send(response);
+        }
+    }
+}
+
+impl fmt::Debug for Reply {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Reply")
+    }
+}
+
+/// Whether another process answers on the socket
+pub fn is_running() -> bool {
+    UnixStream::connect(socket_path()).is_ok()
+}
+
+/// Accepts requests and forwards them to the ui through `tx`
+pub fn spawn_server(mut tx: mpsc::Sender<Message>) -> color_eyre::Result<()> {
+    let path = socket_path();
+    if is_running() {
+        return Err(eyre!("Another instance is already running"))
+            .with_note(|| format!("It listens on: {}", path.display()));
+    }
+    // left behind by an instance that did not shut down cleanly
+    match fs::remove_file(&path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => {
+            return Err(e).wrap_err("Could not remove old control socket")
+        }
+        _ => (),
+    }
+    let listener = UnixListener::bind(&path).wrap_err_with(|| {
+        format!("Could not create control socket at: {}", path.display())
+    })?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let res = stream
+                .wrap_err("Could not accept connection")
+                .and_then(|stream| handle(stream, &mut tx));
+            if let Err(e) = res {
+                eprintln!("Error handling control request: {e:?}");
+            }
+        }
+    });
+    Ok(())
+}
+
+fn handle(
+    stream: UnixStream,
+    tx: &mut mpsc::Sender<Message>,
+) -> color_eyre::Result<()> {
+    // requests are handled one at a time, a client that does not send
+    // or read must not block the others
+    stream
+        .set_read_timeout(Some(Duration::from_secs(1)))
+        .and_then(|()| stream.set_write_timeout(Some(Duration::from_secs(1))))
+        .wrap_err("Could not set timeouts on connection")?;
+    let mut line = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut line)
+        .wrap_err("Could not read request")?;
+    let request = serde_json::from_str(&line).wrap_err("Invalid request")?;
+
+    let (reply_tx, reply_rx) = std_mpsc::channel();
+    tx.try_send(Message::Control {
+        request,
+        reply: Reply(Arc::new(Mutex::new(Some(reply_tx)))),
+    })
+    .wrap_err("Could not pass request to the ui")?;
+    let response = reply_rx
+        .recv_timeout(Duration::from_secs(5))
+        .unwrap_or_else(|_| {
+            Response::Error("Timed out waiting for the ui".to_owned())
+        });
+    write_line(&stream, &response).wrap_err("Could not send response")
+}
+
+/// Sends a request to the running instance and waits for the response
+pub fn send(request: &Request) -> color_eyre::Result<Response> {
+    let stream = UnixStream::connect(socket_path())
+        .wrap_err("Could not connect to break-filler, is it running?")?;
+    write_line(&stream, request).wrap_err("Could not send request")?;
+
+    let mut line = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut line)
+        .wrap_err("Could not read response")?;
+    match serde_json::from_str(&line).wrap_err("Invalid response")? {
+        Response::Error(e) => Err(eyre!(e)),
+        response => Ok(response),
+    }
+}
+
+fn write_line(
+    mut stream: &UnixStream,
+    value: &impl Serialize,
+) -> color_eyre::Result<()> {
+    let mut line = serde_json::to_string(value)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.window {
+            Some(window) => writeln!(f, "window: {window}")?,
+            None => writeln!(f, "no reminders today")?,
+        }
+        match self.period {
+            Some(period) => {
+                writeln!(f, "period: {} minutes", period.as_secs() / 60)?
+            }
+            None => writeln!(f, "period: not yet received")?,
+        }
+        if let Some(pause) = &self.pause {
+            writeln!(f, "{pause}")?;
+        }
+
+        let width = self
+            .activities
+            .iter()
+            .map(|status| status.activity.chars().count())
+            .chain(["activity".len()])
+            .max()
+            .expect("chain has at least one element");
+        write!(
+            f,
+            "\n{:<width$} {:>5} {:>9}  next reminder",
+            "activity", "done", "remaining"
+        )?;
+        for activity in &self.activities {
+            let remaining = activity
+                .remaining
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| "-".to_owned());
+            let next = activity
+                .next_reminder
+                .as_ref()
+                .map(|at| at.strftime("%H:%M").to_string())
+                .unwrap_or_else(|| "-".to_owned());
+            write!(
+                f,
+                "\n{:<width$} {:>5} {:>9}  {next}",
+                activity.activity, activity.done, remaining
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+
+use jiff::Zoned;
+use serde::Serialize;
+
+/// Why an activity was or was not shown during a break. Fields are None
+/// when the planner decided before it got to computing them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Decision {
+    pub activity: String,
+    pub rule: Rule,
+    /// The moment planning starts from: the end of the break after the
+    /// last reminder, the program start or the window start.
+    pub reference: Option<Zoned>,
+    pub remaining_reps: Option<usize>,
+    /// Breaks that fit in the rest of the window after the reference,
+    /// scaled by the load
+    pub relative_future_breaks: Option<usize>,
+    /// Breaks between reminders
+    pub spacing: Option<f32>,
+    /// Which break this is counting from the reference
+    pub break_number: Option<usize>,
+    pub breaks_after_this: Option<usize>,
+}
+
+impl Decision {
+    pub(crate) fn new(activity: &str, rule: Rule) -> Self {
+        Self {
+            activity: activity.to_owned(),
+            rule,
+            reference: None,
+            remaining_reps: None,
+            relative_future_breaks: None,
+            spacing: None,
+            break_number: None,
+            breaks_after_this: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// Reminders are paused
+    Paused,
+    /// The schedule disables reminders today
+    DayDisabled,
+    /// The schedule does not list the activity today
+    NotScheduled,
+    /// Now is outside the (activity's) window
+    OutsideWindow,
+    /// Snoozed during an earlier break, shown again
+    Snoozed,
+    /// Done as often as needed today
+    AllDone,
+    /// Skipped as often as it still needs to be done
+    GaveUpAfterSkips,
+    /// There is plenty of time left, wait for the next break
+    FirstBreak,
+    /// One more to go with two breaks left, wait for the last one
+    WaitForLastBreak,
+    /// Not yet due according to the spacing
+    NotYet,
+    /// Due according to the spacing
+    Due,
+    /// Would be shown but a skip condition holds and there is time left
+    /// to show it later.
+    SkippedForCondition,
+    /// Would be shown but there is a meeting and time left to show it
+    /// later.
+    SkippedForMeeting,
+    /// Would be shown but its skip policy is always and there is time
+    /// left to show it later.
+    SkippedByPolicy,
+}
+
+impl Rule {
+    pub fn shown(self) -> bool {
+        matches!(self, Rule::Snoozed | Rule::Due)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Rule::Paused => "reminders are paused",
+            Rule::DayDisabled => "reminders are disabled today",
+            Rule::NotScheduled => "not scheduled today",
+            Rule::OutsideWindow => "outside its window",
+            Rule::Snoozed => "shown, was snoozed",
+            Rule::AllDone => "done often enough",
+            Rule::GaveUpAfterSkips => "skipped too often, given up on",
+            Rule::FirstBreak => "first break and plenty of time left",
+            Rule::WaitForLastBreak => "waiting for the last break",
+            Rule::NotYet => "not yet due",
+            Rule::Due => "shown, due",
+            Rule::SkippedForCondition => {
+                "due but skipped as a skip condition holds"
+            }
+            Rule::SkippedForMeeting => "due but skipped during a meeting",
+            Rule::SkippedByPolicy => "due but skipped, its policy is always",
+        })
+    }
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.activity, self.rule)?;
+        let Some(reference) = &self.reference else {
+            return Ok(());
+        };
+        write!(f, " (reference: {}", reference.strftime("%H:%M"))?;
+        if let Some(reps) = self.remaining_reps {
+            write!(f, ", remaining: {reps}")?;
+        }
+        if let Some(breaks) = self.relative_future_breaks {
+            write!(f, ", future breaks: {breaks}")?;
+        }
+        if let Some(spacing) = self.spacing {
+            write!(f, ", spacing: {spacing:.2}")?;
+        }
+        if let Some(number) = self.break_number {
+            write!(f, ", break number: {number}")?;
+        }
+        if let Some(after) = self.breaks_after_this {
+            write!(f, ", breaks after this: {after}")?;
+        }
+        f.write_str(")")
+    }
+}
@@ -0,0 +1,171 @@
+use std::io::Write;
+use std::time::Duration;
+
+use color_eyre::eyre::Context;
+use serde::Serialize;
+
+use crate::history::{Event, EventKind};
+use crate::stats::{occurrences, Outcome};
+
+/// An event flattened such that it fits in a csv row
+#[derive(Debug, Serialize)]
+struct Row<'a> {
+    at: &'a jiff::Zoned,
+    event: &'static str,
+    activity: Option<&'a str>,
+    reason: Option<&'a str>,
+    break_duration_secs: Option<u64>,
+    work_duration_secs: Option<u64>,
+    until: Option<&'a jiff::Zoned>,
+}
+
+impl<'a> From<&'a Event> for Row<'a> {
+    fn from(Event { at, kind }: &'a Event) -> Self {
+        let mut row = Row {
+            at,
+            event: "",
+            activity: kind.activity(),
+            reason: None,
+            break_duration_secs: None,
+            work_duration_secs: None,
+            until: None,
+        };
+        row.event = match kind {
+            EventKind::Shown { .. } => "shown",
+            EventKind::Confirmed { .. } => "confirmed",
+            EventKind::AutoCompleted { .. } => "auto-completed",
+            EventKind::Skipped { reason, .. } => {
+                row.reason = reason.as_deref();
+                "skipped"
+            }
+            EventKind::Snoozed { .. } => "snoozed",
+            EventKind::BreakStarted => "break-started",
+            EventKind::BreakEnded => "break-ended",
+            EventKind::ParameterChange {
+                break_duration,
+                work_duration,
+            } => {
+                row.break_duration_secs = Some(break_duration.as_secs());
+                row.work_duration_secs = Some(work_duration.as_secs());
+                "parameter-change"
+            }
+            EventKind::Paused { until } => {
+                row.until = until.as_ref();
+                "paused"
+            }
+            EventKind::Resumed => "resumed",
+            EventKind::WentIdle => "went-idle",
+            EventKind::Returned { long: false } => "returned",
+            EventKind::Returned { long: true } => "returned-after-long-idle",
+            EventKind::Credited { .. } => "credited",
+        };
+        row
+    }
+}
+
+pub fn csv(events: &[Event], out: impl Write) -> color_eyre::Result<()> {
+    let mut writer = ::csv::Writer::from_writer(out);
+    for event in events {
+        writer
+            .serialize(Row::from(event))
+            .wrap_err("Could not write csv row")?;
+    }
+    writer.flush().wrap_err("Could not flush csv")
+}
+
+pub fn json_lines(
+    events: &[Event],
+    mut out: impl Write,
+) -> color_eyre::Result<()> {
+    for event in events {
+        serde_json::to_writer(&mut out, &Row::from(event))
+            .wrap_err("Could not write json")?;
+        writeln!(out).wrap_err("Could not write newline")?;
+    }
+    out.flush().wrap_err("Could not flush json")
+}
+
+/// Every activity that was done becomes a calendar event lasting from
+/// when it was shown until it was confirmed.
+pub fn icalendar(
+    events: &[Event],
+    mut out: impl Write,
+) -> color_eyre::Result<()> {
+    // lines must end with CRLF according to RFC 5545
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        format!("PRODID:-//{}//EN", env!("CARGO_PKG_NAME")),
+    ];
+    for occurrence in occurrences(events) {
+        if !occurrence.outcome.is_done() {
+            continue;
+        }
+
+        let start = occurrence.shown_at.timestamp();
+        let min_end = start
+            .checked_add(Duration::from_secs(60))
+            .wrap_err("Event start too close to the end of time")?;
+        let end = match (occurrence.outcome, occurrence.decided_at) {
+            (Outcome::Confirmed, Some(decided)) => {
+                decided.timestamp().max(min_end)
+            }
+            _ => min_end,
+        };
+        let format = |t: jiff::Timestamp| t.strftime("%Y%m%dT%H%M%SZ");
+        lines.extend([
+            "BEGIN:VEVENT".to_owned(),
+            format!(
+                "UID:{}-{}@{}",
+                start.as_second(),
+                uid_part(&occurrence.activity),
+                env!("CARGO_PKG_NAME")
+            ),
+            format!("DTSTAMP:{}", format(start)),
+            format!("DTSTART:{}", format(start)),
+            format!("DTEND:{}", format(end)),
+            format!("SUMMARY:{}", escape(&occurrence.activity)),
+            "END:VEVENT".to_owned(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    for line in lines {
+        write!(out, "{}\r\n", fold(&line)).wrap_err("Could not write ics")?;
+    }
+    out.flush().wrap_err("Could not flush ics")
+}
+
+/// Identifiers are kept to characters that need no escaping
+fn uid_part(activity: &str) -> String {
+    activity
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Text values can not contain line breaks, they are written as `\n`
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+/// Lines may be at most 75 octets long, longer ones continue on the
+/// next line after a space. Multi byte characters are not split.
+fn fold(line: &str) -> String {
+    const MAX_OCTETS: usize = 75;
+    let mut res = String::with_capacity(line.len());
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > MAX_OCTETS {
+            res.push_str("\r\n ");
+            line_len = 1;
+        }
+        res.push(c);
+        line_len += c.len_utf8();
+    }
+    res
+}
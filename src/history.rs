@@ -0,0 +1,136 @@
+use std::ops::{Bound, RangeBounds};
+use std::time::Duration;
+
+use color_eyre::eyre::{Context, OptionExt};
+use jiff::civil::Date;
+use serde::{Deserialize, Serialize};
+
+use crate::Store;
+
+/// Something that happened, stored forever unlike the counts that are
+/// reset at the start of every day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    pub at: jiff::Zoned,
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventKind {
+    /// the activity was in the popup
+    Shown {
+        activity: String,
+    },
+    /// the user ticked the checkbox of the activity
+    Confirmed {
+        activity: String,
+    },
+    Skipped {
+        activity: String,
+        reason: Option<String>,
+    },
+    Snoozed {
+        activity: String,
+    },
+    BreakStarted,
+    BreakEnded,
+    ParameterChange {
+        break_duration: Duration,
+        work_duration: Duration,
+    },
+    /// the activity does not need confirmation and was counted as done
+    /// when it was shown. A later skip or snooze undoes that.
+    AutoCompleted {
+        activity: String,
+    },
+    /// no reminders until the given time or until resumed if None
+    Paused {
+        until: Option<jiff::Zoned>,
+    },
+    Resumed,
+    /// break-enforcer noticed the user stopped using the computer
+    WentIdle,
+    /// the user came back, long if away long enough for a long break
+    Returned {
+        long: bool,
+    },
+    /// counted as done because the user was away for a long time
+    Credited {
+        activity: String,
+    },
+}
+
+impl EventKind {
+    /// The activity the event is about if any
+    pub fn activity(&self) -> Option<&str> {
+        match self {
+            EventKind::Shown { activity }
+            | EventKind::Confirmed { activity }
+            | EventKind::Skipped { activity, .. }
+            | EventKind::Snoozed { activity }
+            | EventKind::AutoCompleted { activity }
+            | EventKind::Credited { activity } => Some(activity),
+            EventKind::BreakStarted
+            | EventKind::BreakEnded
+            | EventKind::ParameterChange { .. }
+            | EventKind::Paused { .. }
+            | EventKind::Resumed
+            | EventKind::WentIdle
+            | EventKind::Returned { .. } => None,
+        }
+    }
+}
+
+impl Store {
+    pub fn record(
+        &self,
+        at: jiff::Zoned,
+        kind: EventKind,
+    ) -> color_eyre::Result<()> {
+        let event = Event { at, kind };
+        self.history()
+            .push(&event)
+            .wrap_err("Could not append event to history")
+    }
+
+    /// All events that happened on the given dates in order of
+    /// occurrence. Events are appended as they happen so the history is
+    /// sorted, the first one in range is found by bisecting.
+    pub fn events_between(
+        &self,
+        dates: impl RangeBounds<Date>,
+    ) -> color_eyre::Result<Vec<Event>> {
+        let history = self.history();
+        let event = |idx: usize| {
+            history
+                .get(idx)
+                .wrap_err("Could not read event from history")?
+                .ok_or_eyre("Event missing from history")
+        };
+        let before_range = |date: &Date| match dates.start_bound() {
+            Bound::Included(start) => date < start,
+            Bound::Excluded(start) => date <= start,
+            Bound::Unbounded => false,
+        };
+
+        let (mut low, mut high) = (0, history.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if before_range(&event(mid)?.at.date()) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        let mut res = Vec::new();
+        for idx in low..history.len() {
+            let event = event(idx)?;
+            if !dates.contains(&event.at.date()) {
+                break;
+            }
+            res.push(event);
+        }
+        Ok(res)
+    }
+}
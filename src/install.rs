@@ -1,28 +1,26 @@
-use std::ops::Range;
-
 use color_eyre::eyre::Context;
 use color_eyre::Section;
 
 use crate::cli::RunArgs;
-use break_filler::Activity;
-
-fn into_argument(activity: Activity) -> String {
-    activity.description + ":" + &activity.count.to_string()
-}
-
-fn time_argument(window: Range<jiff::civil::Time>) -> String {
-    format!(
-        "{}..{}",
-        window.start.strftime("%H:%M"),
-        window.end.strftime("%H:%M")
-    )
-}
+use break_filler::config::{Config, ConfigFile};
 
 pub fn add_or_modify(args: RunArgs) -> color_eyre::Result<()> {
     let display_env_value = std::env::var("WAYLAND_DISPLAY")
         .wrap_err("Could not get current display.")
         .note("Only wayland is supported by the installer")?;
 
+    let config_path = args.config_args.config_path()?;
+    let config = Config::resolve(&args.config_args)
+        .wrap_err("Config is invalid, fix it before installing")?;
+    if args.config_args.overrides_config() || !config_path.exists() {
+        ConfigFile::from(config)
+            .save(&config_path)
+            .wrap_err("Could not write arguments to config file")?;
+    }
+    let config_path = config_path
+        .canonicalize()
+        .wrap_err("Could not get absolute path to config file")?;
+
     let steps = service_install::install_user!()
         .current_exe()
         .unwrap()
@@ -31,21 +29,9 @@ pub fn add_or_modify(args: RunArgs) -> color_eyre::Result<()> {
         .description("Shows reminders during break-enforcer breaks")
         .env_var("WAYLAND_DISPLAY", display_env_value)
         .arg("run")
-        .args(
-            args.activity
-                .into_iter()
-                .map(into_argument)
-                .flat_map(|a| ["--activity".to_string(), a]),
-        )
-        .arg("--window")
-        .arg(time_argument(args.window))
-        .arg("--load")
-        .arg(args.load.to_string())
-        .args(
-            args.skip_when_visible
-                .into_iter()
-                .flat_map(|a| ["--skip-when-visible".to_string(), a]),
-        )
+        .arg("--config")
+        .arg(config_path)
+        .args(args.source_args())
         .overwrite_existing(true)
         .prepare_install()
         .wrap_err("Could not prepare for install")?;
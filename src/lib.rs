@@ -1,27 +1,59 @@
-use std::ops::Range;
-use std::thread;
+use std::fmt;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use break_enforcer::StateUpdate;
-use cli::TestArgs;
-use color_eyre::eyre::Context;
-use iced::futures::channel::mpsc;
-use jiff::civil::Time;
+use calendar::Calendar;
+use color_eyre::eyre::{eyre, Context};
+use control::{ActivityStatus, Status};
+use explain::{Decision, Rule};
+use history::EventKind;
+use jiff::civil::Date;
+use schedule::Schedule;
+use serde::{Deserialize, Serialize};
+use time::Clock;
+use window::Window;
 
+pub mod calendar;
 pub mod cli;
+pub mod config;
+pub mod control;
+pub mod explain;
+pub mod export;
+pub mod history;
+pub mod record;
+pub mod schedule;
+pub mod script;
+pub mod simulate;
+pub mod skip;
+pub mod source;
+pub mod standalone;
+pub mod stats;
 pub mod time;
 pub mod ui;
+pub mod window;
 pub mod window_manager;
 
 pub type Reminder = String;
 #[dbstruct::dbstruct(db=sled)]
 pub struct Store {
     /// when the reminder was last issued
-    reminder_last_at: HashMap<Reminder, jiff::Zoned>,
-    /// total amount the reminder has been issued since window start
+    pub reminder_last_at: HashMap<Reminder, jiff::Zoned>,
+    /// total amount the reminder has been done since window start
     reminder_counts: HashMap<Reminder, usize>,
+    /// total amount the reminder has been issued since window start, done
+    /// or not
+    shown_counts: HashMap<Reminder, usize>,
+    /// total amount the reminder has been skipped since window start
+    skip_counts: HashMap<Reminder, usize>,
+    /// the reason given the last time the reminder was skipped
+    skip_reasons: HashMap<Reminder, String>,
+    /// the number of breaks had when the reminder was snoozed, removed
+    /// once it is issued again
+    snoozed_at: HashMap<Reminder, usize>,
+    /// total amount the reminder has been snoozed since window start
+    snooze_counts: HashMap<Reminder, usize>,
 
-    /// if this was before the window start we wipe the
+    /// if this was before the start of the day we wipe the
     /// reminder data and breaks
     #[dbstruct(Default)]
     pub last_check: jiff::Zoned,
@@ -29,23 +61,119 @@ pub struct Store {
     /// breaks since the window started
     #[dbstruct(Default)]
     breaks: usize,
+
+    /// everything that happened, never cleared
+    history: Vec<history::Event>,
+
+    /// no reminders while set, kept across days
+    #[dbstruct(Default)]
+    pause: Option<Pause>,
+    /// when the last pause ended, reminders are spaced from here
+    #[dbstruct(Default)]
+    resumed_at: Option<jiff::Zoned>,
 }
 
 pub struct Planner {
     pub load: f32,
     pub store: Store,
     pub activities: Vec<Activity>,
-    pub window: Range<jiff::civil::Time>,
+    pub window: Window,
+    pub schedule: Schedule,
     pub period: Option<Duration>,
     pub break_duration: Option<Duration>,
     pub program_start: jiff::Zoned,
+    pub clock: Arc<dyn Clock>,
+    /// meetings, reminders wait during them and the time they take up
+    /// is not counted as time left for reminders
+    pub calendar: Calendar,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct Activity {
     pub description: String,
+    /// leaving this out of the config means the activity is issued
+    /// every break
+    #[serde(default = "every_break", skip_serializing_if = "is_every_break")]
     pub count: usize,
+    #[serde(default)]
     pub needs_confirm: bool,
+    /// only issue the activity between these times instead of during
+    /// the planners window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<Window>,
+    /// what to do when the user returns after a long time away
+    #[serde(default, skip_serializing_if = "IdleAction::is_ignore")]
+    pub idle: IdleAction,
+    /// whether the activity may wait for a later break
+    #[serde(default, skip_serializing_if = "SkipPolicy::is_inherit")]
+    pub skip: SkipPolicy,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum IdleAction {
+    #[default]
+    Ignore,
+    /// show it on return, for example drink something
+    Offer,
+    /// count it as done, for example a walk
+    Credit,
+}
+
+impl IdleAction {
+    fn is_ignore(&self) -> bool {
+        *self == IdleAction::Ignore
+    }
+}
+
+/// Whether an activity that is due can wait for a later break. It is
+/// always shown once there are not enough breaks left.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkipPolicy {
+    /// wait while the skip-when conditions hold
+    #[default]
+    Inherit,
+    /// always wait, only show it when there are no breaks to spare
+    Always,
+    /// never wait, for example stretching your legs is fine during a
+    /// video
+    Never,
+}
+
+impl SkipPolicy {
+    fn is_inherit(&self) -> bool {
+        *self == SkipPolicy::Inherit
+    }
+
+    /// The rule explaining why a due activity waits, if it does
+    fn holds_back(
+        self,
+        in_meeting: bool,
+        should_skip_if_reasonable: bool,
+    ) -> Option<Rule> {
+        match self {
+            SkipPolicy::Inherit if in_meeting => Some(Rule::SkippedForMeeting),
+            SkipPolicy::Inherit => {
+                should_skip_if_reasonable.then_some(Rule::SkippedForCondition)
+            }
+            SkipPolicy::Always => Some(Rule::SkippedByPolicy),
+            SkipPolicy::Never => None,
+        }
+    }
+}
+
+fn every_break() -> usize {
+    usize::MAX
+}
+
+fn is_every_break(count: &usize) -> bool {
+    *count == usize::MAX
 }
 
 #[derive(Debug, Clone)]
@@ -60,27 +188,117 @@ pub enum Message {
         activity: String,
         at: Instant,
     },
+    Snoozed {
+        activity: String,
+    },
+    SkipReasonChanged {
+        activity: String,
+        reason: String,
+    },
+    Skipped {
+        activity: String,
+    },
+    /// The user stopped using the computer
+    WentIdle,
+    /// The user came back, long enough away for break-enforcer to restart
+    /// the work period
+    Reset,
+    /// Same as reset but away for much longer
+    LongReset,
+    /// A request from another process over the control socket
+    Control {
+        request: control::Request,
+        reply: control::Reply,
+    },
+    /// The skip conditions have been checked, the reminders waiting on
+    /// that can be shown
+    SkipChecked {
+        should_skip_if_reasonable: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Pause {
+    Until(jiff::Zoned),
+    UntilResumed,
+}
+
+impl fmt::Display for Pause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pause::Until(until) => {
+                write!(f, "paused until {}", until.strftime("%H:%M"))
+            }
+            Pause::UntilResumed => f.write_str("paused until resumed"),
+        }
+    }
+}
+
+/// What happened to an activity since the window started
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivityCounts {
+    pub shown: usize,
+    pub done: usize,
+    pub skipped: usize,
+    pub snoozed: usize,
+}
+
+/// The part of the schedule that applies right now
+struct Today<'a> {
+    /// the day the window started, for overnight windows this can
+    /// be yesterday
+    date: Date,
+    window: Window,
+    activities: Vec<&'a Activity>,
 }
 
 impl Planner {
-    fn init_store(&self) -> color_eyre::Result<()> {
+    /// Whether the counts in the store are from before the window
+    /// started, [`Self::init_store`] clears them.
+    fn is_new_day(&self, today: &Today) -> color_eyre::Result<bool> {
         let last_check = self
             .store
             .last_check()
             .get()
             .wrap_err("Could not get last check from db")?;
-        if self.enabled() && self.window_start() > last_check {
+        let day_start = self.day_start(today);
+        Ok(day_start <= self.clock.now() && day_start > last_check)
+    }
+
+    fn init_store(&self, today: &Today) -> color_eyre::Result<()> {
+        if self.is_new_day(today)? {
             self.store
                 .reminder_counts()
                 .clear()
                 .wrap_err("clearing reminder_counts")?;
+            self.store
+                .shown_counts()
+                .clear()
+                .wrap_err("clearing shown_counts")?;
+            self.store
+                .skip_counts()
+                .clear()
+                .wrap_err("clearing skip_counts")?;
+            self.store
+                .skip_reasons()
+                .clear()
+                .wrap_err("clearing skip_reasons")?;
+            self.store
+                .snoozed_at()
+                .clear()
+                .wrap_err("clearing snoozed_at")?;
+            self.store
+                .snooze_counts()
+                .clear()
+                .wrap_err("clearing snooze_counts")?;
             self.store
                 .breaks()
                 .set(&0)
                 .wrap_err("clearing breaks had")?;
             self.store
                 .last_check()
-                .set(&time::zoned_now())
+                .set(&self.clock.now())
                 .wrap_err("updating last_checked")?;
         }
 
@@ -104,17 +322,72 @@ impl Planner {
         }
     }
 
-    fn enabled(&self) -> bool {
-        let now = time::zoned_now();
+    fn enabled(&self, window: &Window) -> bool {
+        window.contains(self.clock.now().time())
+    }
 
-        if self.window.start < self.window.end {
-            // like 12:00..18:00
-            self.window.contains(&now.time())
+    /// activities with their own window use that instead of the days
+    fn window_for<'a>(
+        &self,
+        activity: &'a Activity,
+        today: &'a Today,
+    ) -> &'a Window {
+        activity.window.as_ref().unwrap_or(&today.window)
+    }
+
+    /// None if the schedule has no reminders today
+    fn today(&self) -> Option<Today<'_>> {
+        let now = self.clock.now();
+        let yesterday = now
+            .date()
+            .yesterday()
+            .expect("we are not at the start of time");
+        let window = self.schedule.window_on(yesterday, &self.window);
+        let continues_from_yesterday =
+            window.overnight_end().is_some_and(|end| now.time() < end);
+        let date = if self.schedule.enabled_on(yesterday)
+            && continues_from_yesterday
+        {
+            yesterday
         } else {
-            // like 23:00..01:00
-            (self.window.start..Time::midnight()).contains(&now.time())
-                || (Time::midnight()..self.window.end).contains(&now.time())
+            now.date()
+        };
+
+        if !self.schedule.enabled_on(date) {
+            return None;
         }
+
+        Some(Today {
+            date,
+            window: self.schedule.window_on(date, &self.window).clone(),
+            activities: self
+                .activities
+                .iter()
+                .filter(|a| self.schedule.includes(date, &a.description))
+                .collect(),
+        })
+    }
+
+    /// The earliest start of the window or any of the activities
+    /// windows today.
+    fn day_start(&self, today: &Today) -> jiff::Zoned {
+        let earliest = today
+            .activities
+            .iter()
+            .filter_map(|activity| activity.window.as_ref())
+            .chain([&today.window])
+            .map(Window::start)
+            .min()
+            .expect("chain contains at least the days window");
+        self.clock
+            .now()
+            .with()
+            .date(today.date)
+            .time(earliest)
+            .build()
+            .unwrap_or_else(|_| {
+                panic!("time: {earliest} does not exist on {}", today.date)
+            })
     }
 
     fn period(&self) -> Duration {
@@ -137,78 +410,590 @@ impl Planner {
         &self,
         should_skip_if_reasonable: bool,
     ) -> color_eyre::Result<Vec<Activity>> {
-        self.init_store().wrap_err("Could not init store")?;
+        self.reminder_explained(should_skip_if_reasonable)
+            .map(|(reminders, _)| reminders)
+    }
 
+    /// Same as [`Self::reminder`] but also returns why each activity was
+    /// or was not shown.
+    pub fn reminder_explained(
+        &self,
+        should_skip_if_reasonable: bool,
+    ) -> color_eyre::Result<(Vec<Activity>, Vec<Decision>)> {
+        self.plan(should_skip_if_reasonable, true)
+    }
+
+    /// What a break right now would show, for a manual trigger. Does not
+    /// count as a break, what is shown is recorded as usual.
+    pub fn triggered_explained(
+        &self,
+        should_skip_if_reasonable: bool,
+    ) -> color_eyre::Result<(Vec<Activity>, Vec<Decision>)> {
+        self.plan(should_skip_if_reasonable, false)
+    }
+
+    fn plan(
+        &self,
+        should_skip_if_reasonable: bool,
+        is_break: bool,
+    ) -> color_eyre::Result<(Vec<Activity>, Vec<Decision>)> {
         let mut res = Vec::new();
-        if !self.enabled() {
-            return Ok(res);
+        let mut decisions = Vec::new();
+        if self.pause_state()?.is_some() {
+            decisions.extend(self.activities.iter().map(|activity| {
+                Decision::new(&activity.description, Rule::Paused)
+            }));
+            return Ok((res, decisions));
+        }
+        let Some(today) = self.today() else {
+            decisions.extend(self.activities.iter().map(|activity| {
+                Decision::new(&activity.description, Rule::DayDisabled)
+            }));
+            return Ok((res, decisions));
+        };
+        self.init_store(&today).wrap_err("Could not init store")?;
+
+        decisions.extend(
+            self.activities
+                .iter()
+                .filter(|activity| !today.activities.contains(activity))
+                .map(|activity| {
+                    Decision::new(&activity.description, Rule::NotScheduled)
+                }),
+        );
+        let (enabled, disabled): (Vec<_>, Vec<_>) =
+            today.activities.iter().copied().partition(|activity| {
+                self.enabled(self.window_for(activity, &today))
+            });
+        decisions.extend(disabled.into_iter().map(|activity| {
+            Decision::new(&activity.description, Rule::OutsideWindow)
+        }));
+        if enabled.is_empty() {
+            return Ok((res, decisions));
         }
 
-        let mut can_skip_all = true;
-        let is_first_break = self.store.breaks().get()? == 0;
+        let breaks = self.store.breaks().get()?;
+        let in_meeting = self.calendar.busy_at(&self.clock.now());
+        let is_first_break = breaks == 0;
+
+        for activity in enabled {
+            // snoozed activities go first, they are issued the next break
+            // or if that one is skipped the one after.
+            if let Some(snoozed_at) = self
+                .store
+                .snoozed_at()
+                .get(&activity.description)
+                .wrap_err("could not get snoozed at")?
+            {
+                let must_show = breaks.saturating_sub(snoozed_at) >= 1;
+                let rule = match activity
+                    .skip
+                    .holds_back(in_meeting, should_skip_if_reasonable)
+                {
+                    Some(rule) if !must_show => rule,
+                    _ => {
+                        res.push(activity.clone());
+                        Rule::Snoozed
+                    }
+                };
+                decisions.push(Decision::new(&activity.description, rule));
+                continue;
+            }
 
-        for activity in &self.activities {
-            let remaining_reps =
-                activity.count - self.count_for(&activity.description)?;
+            let window = self.window_for(activity, &today);
+            // saturating as the count can be lowered by a config change
+            let remaining_reps = activity
+                .count
+                .saturating_sub(self.count_for(&activity.description)?);
+            let mut decision =
+                Decision::new(&activity.description, Rule::NotYet);
+            decision.remaining_reps = Some(remaining_reps);
             if remaining_reps < 1 {
+                decision.rule = Rule::AllDone;
+                decisions.push(decision);
+                continue;
+            }
+            // skipped activities are issued again later, unless they have
+            // been skipped as often as they still need to be done. Then we
+            // give up on them for today.
+            if self.counts_for(&activity.description)?.skipped >= remaining_reps
+            {
+                decision.rule = Rule::GaveUpAfterSkips;
+                decisions.push(decision);
                 continue;
             }
 
-            // plan using `max(last reminder, program start, window_start)`
-            // as reference
-            let reference = self
-                .last_reminder(&activity.description)?
-                .zip(self.break_duration)
-                // checked_add can fail if the time does not exist
-                // (winter to summer time for example)
-                .and_then(|(last, break_duration)| {
-                    last.checked_add(break_duration).ok()
-                })
-                .unwrap_or(self.program_start.clone())
-                .max(self.window_start());
-
-            let relative_window =
-                self.window_remaining(&reference).mul_f32(self.load);
-            let relative_future_breaks = relative_window
-                .div_duration_f32(self.period())
-                .floor() as usize;
+            let reference = self.reference(activity, window)?;
+            decision.reference = Some(reference.clone());
+
+            let relative_future_breaks =
+                self.relative_future_breaks(&reference, window);
+            decision.relative_future_breaks = Some(relative_future_breaks);
             if is_first_break && relative_future_breaks / 2 > activity.count {
+                decision.rule = Rule::FirstBreak;
+                decisions.push(decision);
                 continue;
             }
 
-            let break_spacing = (relative_future_breaks) as f32
-                / (remaining_reps.saturating_add(1)) as f32;
+            let break_spacing = spacing(relative_future_breaks, remaining_reps);
             let next_reminder_at = break_spacing;
+            decision.spacing = Some(break_spacing);
 
-            let breaks_after_this = relative_future_breaks
-                .saturating_sub(self.break_number_relative_to(&reference));
+            let break_number =
+                self.break_number_relative_to(&reference, window);
+            let breaks_after_this =
+                relative_future_breaks.saturating_sub(break_number);
+            decision.break_number = Some(break_number);
+            decision.breaks_after_this = Some(breaks_after_this);
             if breaks_after_this == 2 && remaining_reps == 1 {
+                decision.rule = Rule::WaitForLastBreak;
+                decisions.push(decision);
                 continue;
             }
-            if breaks_after_this < remaining_reps {
-                can_skip_all = false;
+            let must_show = breaks_after_this < remaining_reps;
+
+            if next_reminder_at.floor() as usize <= break_number {
+                match activity
+                    .skip
+                    .holds_back(in_meeting, should_skip_if_reasonable)
+                {
+                    Some(rule) if !must_show => decision.rule = rule,
+                    _ => {
+                        res.push(activity.clone());
+                        decision.rule = Rule::Due;
+                    }
+                }
             }
+            decisions.push(decision);
+        }
 
-            if next_reminder_at.floor() as usize
-                <= self.break_number_relative_to(&reference)
-            {
-                res.push(activity.clone());
+        if is_break {
+            self.increment_total_breaks()?;
+        }
+        self.mark_shown(&res)?;
+        Ok((res, decisions))
+    }
+
+    /// Returning after being away is a natural break. After a long time
+    /// away activities configured to be credited are counted as done
+    /// and those to be offered are shown in addition to the usual
+    /// reminders.
+    pub fn returned_explained(
+        &self,
+        long: bool,
+        should_skip_if_reasonable: bool,
+    ) -> color_eyre::Result<(Vec<Activity>, Vec<Decision>)> {
+        let today = self.today();
+        let long = long && self.pause_state()?.is_none();
+        if let Some(today) = today.as_ref().filter(|_| long) {
+            // before crediting, it could be the first event of the day
+            self.init_store(today).wrap_err("Could not init store")?;
+            for activity in &today.activities {
+                if activity.idle == IdleAction::Credit
+                    && self.remaining_reps(activity)? > 0
+                {
+                    self.record(EventKind::Credited {
+                        activity: activity.description.clone(),
+                    })?;
+                    self.count_completed(&activity.description)?;
+                }
             }
         }
 
-        self.increment_total_breaks()?;
+        let (mut res, decisions) =
+            self.reminder_explained(should_skip_if_reasonable)?;
+        let Some(today) = today.filter(|_| long) else {
+            return Ok((res, decisions));
+        };
 
-        if can_skip_all && should_skip_if_reasonable {
-            return Ok(Vec::new());
+        let mut offered = Vec::new();
+        for activity in &today.activities {
+            if activity.idle == IdleAction::Offer
+                && self.enabled(self.window_for(activity, &today))
+                && self.remaining_reps(activity)? > 0
+                && !res
+                    .iter()
+                    .any(|due| due.description == activity.description)
+            {
+                offered.push((*activity).clone());
+            }
         }
+        self.mark_shown(&offered)?;
+        res.extend(offered);
+        Ok((res, decisions))
+    }
 
-        for activity in &res {
+    /// Whether the activity can still be counted as done: it has
+    /// repetitions left today and was not already done since the last
+    /// break started.
+    pub fn is_outstanding(
+        &self,
+        activity: &Activity,
+    ) -> color_eyre::Result<bool> {
+        if self.remaining_reps(activity)? == 0 {
+            return Ok(false);
+        }
+        let events = self.store.events_between(self.clock.now().date()..)?;
+        let done_this_break = events
+            .iter()
+            .rev()
+            .take_while(|event| event.kind != EventKind::BreakStarted)
+            .filter(|event| {
+                event.kind.activity() == Some(activity.description.as_str())
+            })
+            // a skip or snooze undoes an auto completion
+            .find_map(|event| match event.kind {
+                EventKind::Confirmed { .. }
+                | EventKind::AutoCompleted { .. }
+                | EventKind::Credited { .. } => Some(true),
+                EventKind::Skipped { .. } | EventKind::Snoozed { .. } => {
+                    Some(false)
+                }
+                _ => None,
+            })
+            .unwrap_or(false);
+        Ok(!done_this_break)
+    }
+
+    fn remaining_reps(&self, activity: &Activity) -> color_eyre::Result<usize> {
+        Ok(activity
+            .count
+            .saturating_sub(self.counts_for(&activity.description)?.done))
+    }
+
+    fn mark_shown(&self, activities: &[Activity]) -> color_eyre::Result<()> {
+        for activity in activities {
+            let shown = self
+                .store
+                .shown_counts()
+                .get(&activity.description)
+                .wrap_err("getting shown count")?
+                .unwrap_or(0);
+            self.store
+                .shown_counts()
+                .insert(&activity.description, &(shown + 1))
+                .wrap_err("setting shown count")?;
+            self.record(EventKind::Shown {
+                activity: activity.description.clone(),
+            })?;
+            self.store
+                .snoozed_at()
+                .remove(&activity.description)
+                .wrap_err("could not clear snooze")?;
             if !activity.needs_confirm {
-                self.mark_completed(&activity.description)?;
+                self.record(EventKind::AutoCompleted {
+                    activity: activity.description.clone(),
+                })?;
+                self.count_completed(&activity.description)?;
             }
         }
+        Ok(())
+    }
+
+    /// Issue the activity again during one of the next two breaks
+    /// without counting it as done or skipped.
+    pub fn snooze(&self, description: &String) -> color_eyre::Result<()> {
+        self.record(EventKind::Snoozed {
+            activity: description.clone(),
+        })?;
+        let breaks = self.store.breaks().get()?;
+        self.store
+            .snoozed_at()
+            .insert(description, &breaks)
+            .wrap_err("setting snoozed at")?;
+
+        let snoozed = self
+            .store
+            .snooze_counts()
+            .get(description)
+            .wrap_err("getting snooze count")?
+            .unwrap_or(0);
+        self.store
+            .snooze_counts()
+            .insert(description, &(snoozed + 1))
+            .wrap_err("setting snooze count")?;
+
+        self.undo_completed_on_issue(description)
+    }
+
+    /// Do not issue the activity now. It is not counted as done and is
+    /// spaced out over the rest of the window again.
+    pub fn skip(
+        &self,
+        description: &String,
+        reason: Option<String>,
+    ) -> color_eyre::Result<()> {
+        self.record(EventKind::Skipped {
+            activity: description.clone(),
+            reason: reason.clone(),
+        })?;
+        let skipped = self
+            .store
+            .skip_counts()
+            .get(description)
+            .wrap_err("getting skip count")?
+            .unwrap_or(0);
+        self.store
+            .skip_counts()
+            .insert(description, &(skipped + 1))
+            .wrap_err("setting skip count")?;
+        if let Some(reason) = reason {
+            self.store
+                .skip_reasons()
+                .insert(description, &reason)
+                .wrap_err("setting skip reason")?;
+        }
+
+        self.undo_completed_on_issue(description)?;
+        // space the next reminder from now on
+        self.store
+            .reminder_last_at()
+            .insert(description, &self.clock.now())
+            .wrap_err("setting last at")?;
+        Ok(())
+    }
+
+    /// Activities that do not need confirmation are counted as completed as
+    /// soon as they are issued. That also moved when it was last done,
+    /// which is set back to the completion before.
+    fn undo_completed_on_issue(
+        &self,
+        description: &String,
+    ) -> color_eyre::Result<()> {
+        let needs_confirm = self
+            .activities
+            .iter()
+            .find(|activity| &activity.description == description)
+            .is_some_and(|activity| activity.needs_confirm);
+        if needs_confirm {
+            return Ok(());
+        }
 
-        Ok(res)
+        let count = self.count_for(description)?;
+        self.store
+            .reminder_counts()
+            .insert(description, &count.saturating_sub(1))
+            .wrap_err("setting count")?;
+        match self.previous_last_at(description)? {
+            Some(at) => self
+                .store
+                .reminder_last_at()
+                .insert(description, &at)
+                .wrap_err("setting last at")?,
+            None => self
+                .store
+                .reminder_last_at()
+                .remove(description)
+                .wrap_err("clearing last at")?,
+        };
+        Ok(())
+    }
+
+    /// When the activity was last done or skipped today, not counting auto
+    /// completions undone by a snooze or skip. The latest of those has
+    /// already been recorded.
+    fn previous_last_at(
+        &self,
+        description: &str,
+    ) -> color_eyre::Result<Option<jiff::Zoned>> {
+        let events = self.store.events_between(self.clock.now().date()..)?;
+        let mut undone = 0usize;
+        for event in events.iter().rev() {
+            if event.kind.activity() != Some(description) {
+                continue;
+            }
+            match event.kind {
+                EventKind::Snoozed { .. } => undone += 1,
+                EventKind::AutoCompleted { .. } if undone > 0 => undone -= 1,
+                EventKind::AutoCompleted { .. }
+                | EventKind::Confirmed { .. }
+                | EventKind::Credited { .. }
+                | EventKind::Skipped { .. } => {
+                    return Ok(Some(event.at.clone()))
+                }
+                _ => (),
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn counts_for(
+        &self,
+        description: &str,
+    ) -> color_eyre::Result<ActivityCounts> {
+        Ok(ActivityCounts {
+            shown: self
+                .store
+                .shown_counts()
+                .get(description)
+                .wrap_err("getting shown count")?
+                .unwrap_or(0),
+            done: self
+                .store
+                .reminder_counts()
+                .get(description)
+                .wrap_err("getting count")?
+                .unwrap_or(0),
+            skipped: self
+                .store
+                .skip_counts()
+                .get(description)
+                .wrap_err("getting skip count")?
+                .unwrap_or(0),
+            snoozed: self
+                .store
+                .snooze_counts()
+                .get(description)
+                .wrap_err("getting snooze count")?
+                .unwrap_or(0),
+        })
+    }
+
+    /// Where every activity scheduled today stands. The next reminder is
+    /// an estimate, it assumes no breaks are missed. Only reads the store,
+    /// what a new day or an expired pause would reset is left as is.
+    pub fn status(&self) -> color_eyre::Result<Status> {
+        let now = self.clock.now();
+        let pause = match self.store.pause().get() {
+            Ok(Some(Pause::Until(until))) if until <= now => None,
+            pause => pause.wrap_err("could not get pause")?,
+        };
+        let Some(today) = self.today() else {
+            return Ok(Status {
+                window: None,
+                period: self.period,
+                pause,
+                activities: Vec::new(),
+            });
+        };
+        let new_day = self.is_new_day(&today)?;
+
+        let mut activities = Vec::new();
+        for activity in &today.activities {
+            let done = if new_day {
+                0
+            } else {
+                self.counts_for(&activity.description)?.done
+            };
+            let remaining = (!is_every_break(&activity.count))
+                .then(|| activity.count.saturating_sub(done));
+            let window = self.window_for(activity, &today);
+            let snoozed = !new_day
+                && self
+                    .store
+                    .snoozed_at()
+                    .get(&activity.description)
+                    .wrap_err("could not get snoozed at")?
+                    .is_some();
+
+            let next_reminder = if snoozed {
+                Some(now.clone())
+            } else if remaining == Some(0) || !self.enabled(window) {
+                None
+            } else if let Some(period) = self.period {
+                let reference = self.reference(activity, window)?;
+                let remaining_reps = remaining.unwrap_or(usize::MAX);
+                let spacing = spacing(
+                    self.relative_future_breaks(&reference, window),
+                    remaining_reps,
+                );
+                let breaks_to_wait = (spacing.floor() as u32).saturating_sub(1);
+                reference
+                    .checked_add(period * breaks_to_wait)
+                    .ok()
+                    .map(|at| at.max(now.clone()))
+            } else {
+                None
+            };
+
+            activities.push(ActivityStatus {
+                activity: activity.description.clone(),
+                done,
+                remaining,
+                next_reminder,
+            });
+        }
+
+        Ok(Status {
+            window: Some(today.window),
+            period: self.period,
+            pause,
+            activities,
+        })
+    }
+
+    /// Plan using `max(last reminder, program start, window_start)`
+    /// as reference
+    fn reference(
+        &self,
+        activity: &Activity,
+        window: &Window,
+    ) -> color_eyre::Result<jiff::Zoned> {
+        Ok(self
+            .last_reminder(&activity.description)?
+            .zip(self.break_duration)
+            // checked_add can fail if the time does not exist
+            // (winter to summer time for example)
+            .and_then(|(last, break_duration)| {
+                last.checked_add(break_duration).ok()
+            })
+            .unwrap_or(self.program_start.clone())
+            .max(window.start_before(&self.clock.now()))
+            // re-space what remains after a pause instead of catching up
+            .max(
+                self.store
+                    .resumed_at()
+                    .get()
+                    .wrap_err("could not get resumed at")?
+                    .unwrap_or_default(),
+            ))
+    }
+
+    fn relative_future_breaks(
+        &self,
+        reference: &jiff::Zoned,
+        window: &Window,
+    ) -> usize {
+        // only counts the time within the window, breaks outside of it
+        // do not get reminders
+        let now = self.clock.now();
+        let end = window
+            .occurrence(&now)
+            .pop()
+            .expect("window has at least one range")
+            .end;
+        let relative_window = window
+            .remaining_after(reference, &now)
+            .saturating_sub(self.meeting_time(reference, &end, window))
+            .mul_f32(self.load);
+        relative_window.div_duration_f32(self.period()).floor() as usize
+    }
+
+    /// Time in meetings between `from` and `to` that falls within the
+    /// window. Breaks are not counted during it, like `load` it is time
+    /// away from the desk.
+    fn meeting_time(
+        &self,
+        from: &jiff::Zoned,
+        to: &jiff::Zoned,
+        window: &Window,
+    ) -> Duration {
+        if from >= to {
+            return Duration::ZERO;
+        }
+        let now = self.clock.now();
+        self.calendar
+            .busy_periods(from, to)
+            .iter()
+            .map(|meeting| {
+                window.duration_within(&meeting.start, &meeting.end, &now)
+            })
+            .sum()
+    }
+
+    /// Breaks counted since the window started
+    pub fn breaks(&self) -> color_eyre::Result<usize> {
+        self.store.breaks().get().wrap_err("could not get breaks")
     }
 
     fn increment_total_breaks(&self) -> color_eyre::Result<()> {
@@ -217,18 +1002,87 @@ impl Planner {
         Ok(())
     }
 
-    fn break_number_relative_to(&self, reference: &jiff::Zoned) -> usize {
-        let breaks_elapsed = reference
-            .duration_until(&time::zoned_now())
-            .unsigned_abs()
+    fn break_number_relative_to(
+        &self,
+        reference: &jiff::Zoned,
+        window: &Window,
+    ) -> usize {
+        let now = self.clock.now();
+        let breaks_elapsed = window
+            .duration_within(reference, &now, &now)
+            .saturating_sub(self.meeting_time(reference, &now, window))
             .div_duration_f32(self.period())
             .floor() as usize;
         breaks_elapsed + 1
     }
 
+    /// No reminders until `until` or if that is None until resumed
+    pub fn pause(&self, until: Option<jiff::Zoned>) -> color_eyre::Result<()> {
+        self.record(EventKind::Paused {
+            until: until.clone(),
+        })?;
+        let pause = match until {
+            Some(until) => Pause::Until(until),
+            None => Pause::UntilResumed,
+        };
+        self.store
+            .pause()
+            .set(&Some(pause))
+            .wrap_err("could not store pause")
+    }
+
+    /// Errors if there is no pause, resuming would re-space the
+    /// reminders for nothing
+    pub fn resume(&self) -> color_eyre::Result<()> {
+        if self.pause_state()?.is_none() {
+            return Err(eyre!("Not paused"));
+        }
+        self.end_pause(self.clock.now())
+    }
+
+    fn end_pause(&self, at: jiff::Zoned) -> color_eyre::Result<()> {
+        self.store.record(at.clone(), EventKind::Resumed)?;
+        self.store
+            .pause()
+            .set(&None)
+            .wrap_err("could not clear pause")?;
+        self.store
+            .resumed_at()
+            .set(&Some(at))
+            .wrap_err("could not store resumed at")
+    }
+
+    /// The current pause, one that has run out is ended first
+    pub fn pause_state(&self) -> color_eyre::Result<Option<Pause>> {
+        let pause = self.store.pause().get().wrap_err("could not get pause")?;
+        match pause {
+            Some(Pause::Until(until)) if until <= self.clock.now() => {
+                self.end_pause(until)?;
+                Ok(None)
+            }
+            pause => Ok(pause),
+        }
+    }
+
+    /// Append to the history at the time on the planner's clock
+    pub fn record(&self, kind: EventKind) -> color_eyre::Result<()> {
+        self.store.record(self.clock.now(), kind)
+    }
+
+    /// The user confirmed they did the activity
     pub fn mark_completed(
         &self,
         description: &String,
+    ) -> Result<(), color_eyre::eyre::Error> {
+        self.record(EventKind::Confirmed {
+            activity: description.clone(),
+        })?;
+        self.count_completed(description)
+    }
+
+    fn count_completed(
+        &self,
+        description: &String,
     ) -> Result<(), color_eyre::eyre::Error> {
         if let Some(curr) = self
             .store
@@ -249,7 +1103,7 @@ impl Planner {
 
         self.store
             .reminder_last_at()
-            .insert(description, &time::zoned_now())
+            .insert(description, &self.clock.now())
             .wrap_err("setting last at")?;
         Ok(())
     }
@@ -263,114 +1117,9 @@ impl Planner {
             .get(description)
             .wrap_err("could not get last reminder at from db")
     }
-
-    fn window_start(&self) -> jiff::Zoned {
-        let now = time::zoned_now();
-        if self.window.start <= now.time() {
-            now.with()
-                .time(self.window.start)
-                .build()
-                .unwrap_or_else(|_| {
-                    panic!("time: {} does not exist today", self.window.start)
-                })
-        } else {
-            now.with()
-                .time(self.window.start)
-                .build()
-                .unwrap_or_else(|_| {
-                    panic!("time: {} does not exist today", self.window.start)
-                })
-                .yesterday()
-                .unwrap_or_else(|_| {
-                    panic!(
-                        "time: {} does not exist yesterday",
-                        self.window.start
-                    )
-                })
-        }
-    }
-
-    fn window_end(&self) -> jiff::Zoned {
-        let now = time::zoned_now();
-        if self.window.end >= now.time() {
-            now.with()
-                .time(self.window.end)
-                .build()
-                .unwrap_or_else(|_| {
-                    panic!("time: {} does not exist today", self.window.end)
-                })
-        } else {
-            now.with()
-                .time(self.window.end)
-                .build()
-                .unwrap_or_else(|_| {
-                    panic!("time: {} does not exist today", self.window.end)
-                })
-                .tomorrow()
-                .unwrap_or_else(|_| {
-                    panic!("time: {} does not exist tomorrow", self.window.end)
-                })
-        }
-    }
-
-    fn window_remaining(&self, reference: &jiff::Zoned) -> Duration {
-        reference.duration_until(&self.window_end()).unsigned_abs()
-    }
 }
 
-pub fn spawn_mock_break_enforcer_interface(test_config: TestArgs) {
-    let (mut tx, rx) = mpsc::channel(64);
-    thread::spawn(move || {
-        tx.try_send(Message::ParameterChange {
-            break_duration: test_config.break_duration,
-            work_duration: test_config.work_duration,
-        })
-        .unwrap();
-        thread::sleep(Duration::from_millis(250));
-
-        for i in 0..test_config.periods {
-            eprintln!("sending break start {i}");
-            tx.try_send(Message::BreakStarted).unwrap();
-            thread::sleep(Duration::from_secs(1));
-            time::break_ends();
-            time::next_break();
-            tx.try_send(Message::BreakEnded).unwrap();
-            thread::sleep(Duration::from_secs(1));
-        }
-    });
-    ui::send_rx(rx);
-}
-
-pub fn spawn_break_enforcer_interface() {
-    let (mut tx, rx) = mpsc::channel(64);
-    thread::spawn(move || {
-        let mut api = break_enforcer::ReconnectingApi::new().subscribe();
-        loop {
-            match api.recv_update() {
-                StateUpdate::ParameterChange {
-                    break_duration,
-                    work_duration,
-                } => tx
-                    .try_send(Message::ParameterChange {
-                        break_duration,
-                        work_duration,
-                    })
-                    .expect("cant lag so much that message can not be send"),
-                StateUpdate::BreakStarted => {
-                    tx.try_send(Message::BreakStarted).expect(
-                        "cant lag so much that message can not be send",
-                    );
-                }
-                StateUpdate::BreakEnded => {
-                    tx.try_send(Message::BreakEnded).expect(
-                        "cant lag so much that message can not be send",
-                    );
-                }
-                StateUpdate::WentIdle
-                | StateUpdate::Reset
-                | StateUpdate::LongReset => (),
-            }
-        }
-    });
-    ui::send_rx(rx);
+/// Breaks between reminders
+fn spacing(relative_future_breaks: usize, remaining_reps: usize) -> f32 {
+    relative_future_breaks as f32 / remaining_reps.saturating_add(1) as f32
 }
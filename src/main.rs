@@ -1,81 +1,294 @@
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
-use std::{env, fs, thread};
+use std::{env, fs, process, thread};
 
+use break_filler::cli::{
+    ExportArgs, ExportFormat, ReplayArgs, SimulateArgs, SourceKind, StatsArgs,
+};
+use break_filler::config::{Config, ConfigWatcher};
+use break_filler::control::{self, Request, Response};
+use break_filler::history::Event;
+use break_filler::record::{self, Recorder};
+use break_filler::script::Script;
+use break_filler::source::{self, BreakEnforcer, BreakSource, Mock};
+use break_filler::standalone::Standalone;
 use break_filler::ui::Ui;
-use clap::Parser;
+use break_filler::Message;
 use cli::Cli;
-use color_eyre::eyre::{Context, OptionExt};
-use time::zoned_now;
+use color_eyre::eyre::{eyre, Context, OptionExt};
+use color_eyre::Section;
+use time::{Clock, SimulatedClock, SystemClock};
 
-use break_filler::{
-    cli, spawn_break_enforcer_interface, spawn_mock_break_enforcer_interface,
-    time, Store,
-};
+use break_filler::{cli, time, Store};
 
 mod install;
 
 fn main() -> color_eyre::Result<()> {
-    let cli = Cli::parse();
+    let cli = Cli::parse_checked();
     color_eyre::install().unwrap();
 
-    let (run_args, store) = match cli.command {
+    let ((config, config_watcher), store, clock) = match cli.command {
         cli::Command::Run(run_args) => {
+            let config = ConfigWatcher::load(run_args.config_args.clone())?;
+            let record = run_args.record.as_deref().map(record::open);
+            let record = record.transpose()?;
             // give login process time to complete such that the display
             // server is running when iced starts. (there is no simple way to
             // check that which is why we use a sleep)
             thread::sleep(Duration::from_secs(10));
-            spawn_break_enforcer_interface();
-            #[expect(
-                deprecated,
-                reason = "windows only issue fixed in next rust version"
-            )]
-            let path = env::home_dir()
-                .ok_or_eyre("Could not find home dir")?
-                .join(".local")
-                .join("share")
-                .join(env!("CARGO_PKG_NAME"));
-            fs::create_dir(&path)
-                .accept_kind(std::io::ErrorKind::AlreadyExists)
-                .wrap_err("Could not create directory to store db")?;
-            let store = Store::new(path).wrap_err("Could not open database")?;
-            (run_args, store)
+            let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+            let tx = match run_args.source_kind() {
+                SourceKind::BreakEnforcer => {
+                    spawn_source(BreakEnforcer::connect, record, &clock)
+                }
+                SourceKind::Standalone => {
+                    let work =
+                        run_args.work.unwrap_or(Duration::from_secs(25 * 60));
+                    let break_ =
+                        run_args.break_.unwrap_or(Duration::from_secs(5 * 60));
+                    spawn_source(
+                        move || Standalone::new(work, break_),
+                        record,
+                        &clock,
+                    )
+                }
+                SourceKind::Script => {
+                    let path = run_args
+                        .script
+                        .clone()
+                        .expect("clap requires --script for this source");
+                    let script = Script::open(&path)?;
+                    spawn_source(move || script, record, &clock)
+                }
+            };
+            control::spawn_server(tx)?;
+            let store =
+                Store::new(data_dir()?).wrap_err("Could not open database")?;
+            (config, store, clock)
         }
         cli::Command::Test(test_args) => {
-            time::setup_mock_from_args(&test_args);
-            spawn_mock_break_enforcer_interface(test_args.clone());
-            #[expect(
-                deprecated,
-                reason = "windows only issue fixed in next rust version"
-            )]
-            let path = env::home_dir()
-                .ok_or_eyre("Could not find home dir")?
-                .join(".local")
-                .join("share")
-                .join(env!("CARGO_PKG_NAME"));
-            fs::create_dir(&path)
-                .accept_kind(std::io::ErrorKind::AlreadyExists)
-                .wrap_err("Could not create directory to store db")?;
-            let store = Store::new(path).wrap_err("Could not open database")?;
+            let config =
+                ConfigWatcher::load(test_args.run_args.config_args.clone())?;
+            let clock = SimulatedClock::from_args(&test_args);
+            let record = test_args.run_args.record.as_deref();
+            let record = record.map(record::open).transpose()?;
+            let mock = Mock::new(test_args.clone(), clock.clone());
+            let shared: Arc<dyn Clock> = Arc::new(clock.clone());
+            let tx = spawn_source(move || mock, record, &shared);
+            control::spawn_server(tx)?;
+            let store =
+                Store::new(data_dir()?).wrap_err("Could not open database")?;
             store
                 .last_check()
-                .set(&zoned_now().yesterday().unwrap())
+                .set(&clock.now().yesterday().unwrap())
                 .unwrap();
-            (test_args.run_args, store)
+            (config, store, shared)
         }
         cli::Command::Install(run_args) => {
             return install::add_or_modify(run_args)
         }
         cli::Command::Remove => return install::remove(),
+        cli::Command::Stats(stats_args) => return stats(stats_args),
+        cli::Command::Export(export_args) => return export(export_args),
+        cli::Command::Simulate(simulate_args) => {
+            return simulate(simulate_args)
+        }
+        cli::Command::Replay(replay_args) => return replay(replay_args),
+        cli::Command::Status => return status(),
+        cli::Command::Done { activity } => {
+            return control(Request::Done { activity })
+        }
+        cli::Command::Trigger => return control(Request::Trigger),
+        cli::Command::Pause(args) => {
+            return control(Request::Pause {
+                duration: args.duration,
+            })
+        }
+        cli::Command::Resume => return control(Request::Resume),
     };
 
     iced::daemon(Ui::title, Ui::update, Ui::view)
         .subscription(Ui::subscription)
         .theme(Ui::theme)
         .font(Ui::FONT)
-        .run_with(|| Ui::new(run_args, store))
+        .run_with(|| Ui::new(config, config_watcher, store, clock))
         .wrap_err("Error running UI")
 }
 
+fn data_dir() -> color_eyre::Result<PathBuf> {
+    #[expect(
+        deprecated,
+        reason = "windows only issue fixed in next rust version"
+    )]
+    let path = env::home_dir()
+        .ok_or_eyre("Could not find home dir")?
+        .join(".local")
+        .join("share")
+        .join(env!("CARGO_PKG_NAME"));
+    fs::create_dir(&path)
+        .accept_kind(std::io::ErrorKind::AlreadyExists)
+        .wrap_err("Could not create directory to store db")?;
+    Ok(path)
+}
+
+/// The database can only be opened by one process at the time. While
+/// break-filler is running the history is thus asked from it.
+fn history(since: Option<jiff::civil::Date>) -> color_eyre::Result<Vec<Event>> {
+    if control::is_running() {
+        return match control::send(&Request::History { since })? {
+            Response::History(events) => Ok(events),
+            other => {
+                Err(eyre!("Unexpected answer to history request: {other:?}"))
+                    .note("Is the running instance the same version?")
+            }
+        };
+    }
+
+    let store = Store::new(data_dir()?).wrap_err("Could not open database")?;
+    match since {
+        Some(since) => store.events_between(since..),
+        None => store.events_between(..),
+    }
+}
+
+fn stats(args: StatsArgs) -> color_eyre::Result<()> {
+    let events = history(args.since)?;
+    let stats =
+        break_filler::stats::summarize(&events, args.activity.as_deref());
+
+    if args.json {
+        let json = serde_json::to_string_pretty(&stats)
+            .wrap_err("Could not serialize stats")?;
+        println!("{json}");
+    } else {
+        println!("{stats}");
+    }
+    Ok(())
+}
+
+fn export(args: ExportArgs) -> color_eyre::Result<()> {
+    let events = history(args.since)?;
+
+    let out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(io::BufWriter::new(
+            fs::File::create(path).wrap_err("Could not create output file")?,
+        )),
+        None => Box::new(io::stdout().lock()),
+    };
+    match args.format {
+        ExportFormat::Csv => break_filler::export::csv(&events, out),
+        ExportFormat::Jsonl => break_filler::export::json_lines(&events, out),
+        ExportFormat::Ics => break_filler::export::icalendar(&events, out),
+    }
+}
+
+/// Appends the messages of the source to `record` if it is set, stamped
+/// with the time according to `clock`
+fn spawn_source<S: BreakSource>(
+    make: impl FnOnce() -> S + Send + 'static,
+    record: Option<fs::File>,
+    clock: &Arc<dyn Clock>,
+) -> iced::futures::channel::mpsc::Sender<Message> {
+    let clock = clock.clone();
+    match record {
+        Some(file) => source::spawn(move || Recorder::new(make(), file, clock)),
+        None => source::spawn(make),
+    }
+}
+
+/// Never touches the real database, `purpose` ends up in the name of the
+/// temporary one
+fn with_temp_store<T>(
+    purpose: &str,
+    f: impl FnOnce(Store) -> color_eyre::Result<T>,
+) -> color_eyre::Result<T> {
+    let dir = env::temp_dir().join(format!(
+        "{}-{purpose}-{}",
+        env!("CARGO_PKG_NAME"),
+        process::id()
+    ));
+    let res = Store::new(&dir)
+        .wrap_err("Could not open temporary database")
+        .and_then(f);
+    fs::remove_dir_all(&dir).wrap_err("Could not remove temporary database")?;
+    res
+}
+
+fn simulate(args: SimulateArgs) -> color_eyre::Result<()> {
+    let config = Config::resolve(&args.config_args)?;
+    let date = args.date.unwrap_or_else(|| jiff::Zoned::now().date());
+
+    let breaks = with_temp_store("simulate", |store| {
+        break_filler::simulate::day(
+            config,
+            store,
+            date,
+            args.work_duration,
+            args.break_duration,
+        )
+    })?;
+    if breaks
+        .iter()
+        .all(|simulated| simulated.activities.is_empty())
+    {
+        println!("no breaks with reminders on {date}");
+    }
+    for simulated in breaks {
+        println!("{simulated}");
+        if args.config_args.explain {
+            for decision in simulated.decisions {
+                println!("  {decision}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn replay(args: ReplayArgs) -> color_eyre::Result<()> {
+    let config = Config::resolve(&args.config_args)?;
+    let recording = record::read(&args.recording)?;
+
+    let replayed = with_temp_store("replay", |store| {
+        break_filler::simulate::replay(config, store, &recording)
+    })?;
+    if replayed.is_empty() {
+        println!("no breaks in the recording");
+    }
+    for replayed in replayed {
+        println!("{replayed}");
+        if args.config_args.explain {
+            for decision in replayed.decisions {
+                println!("  {decision}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn status() -> color_eyre::Result<()> {
+    match control::send(&Request::Status)? {
+        Response::Status(status) => println!("{status}"),
+        other => {
+            return Err(eyre!("Unexpected answer to status: {other:?}"))
+                .note("Is the running instance the same version?")
+        }
+    }
+    Ok(())
+}
+
+fn control(request: Request) -> color_eyre::Result<()> {
+    match control::send(&request)? {
+        Response::Ok(message) => println!("{message}"),
+        other => {
+            return Err(eyre!("Unexpected answer to {request:?}: {other:?}"))
+                .note("Is the running instance the same version?")
+        }
+    }
+    Ok(())
+}
+
 trait ResultAcceptKind {
     type Error;
     fn accept_kind(
@@ -0,0 +1,130 @@
+//! Writing the messages of a break source to a file so they can be
+//! replayed later, see [`crate::simulate::replay`]. Every message is a line
+//! of json holding the time it arrived.
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use color_eyre::eyre::Context;
+use jiff::Zoned;
+use serde::{Deserialize, Serialize};
+
+use crate::source::BreakSource;
+use crate::time::Clock;
+use crate::Message;
+
+/// A message from a break source and when it arrived
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recorded {
+    pub at: Zoned,
+    pub message: SourceMessage,
+}
+
+/// The messages a break source can yield
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SourceMessage {
+    ParameterChange {
+        break_duration: Duration,
+        work_duration: Duration,
+    },
+    BreakStarted,
+    BreakEnded,
+    WentIdle,
+    Reset,
+    LongReset,
+}
+
+impl SourceMessage {
+    /// None for messages that do not come from a break source
+    pub fn from_message(message: &Message) -> Option<Self> {
+        Some(match message {
+            Message::ParameterChange {
+                break_duration,
+                work_duration,
+            } => Self::ParameterChange {
+                break_duration: *break_duration,
+                work_duration: *work_duration,
+            },
+            Message::BreakStarted => Self::BreakStarted,
+            Message::BreakEnded => Self::BreakEnded,
+            Message::WentIdle => Self::WentIdle,
+            Message::Reset => Self::Reset,
+            Message::LongReset => Self::LongReset,
+            _ => return None,
+        })
+    }
+}
+
+/// Passes on the messages of another source while appending them to a
+/// file
+pub struct Recorder<S> {
+    source: S,
+    file: fs::File,
+    clock: Arc<dyn Clock>,
+}
+
+impl<S: BreakSource> Recorder<S> {
+    /// The file is best opened with [`open`] before the source is made
+    /// such that a wrong path is reported right away. Messages are stamped
+    /// with the time of `clock`, the same clock the planner uses.
+    pub fn new(source: S, file: fs::File, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            source,
+            file,
+            clock,
+        }
+    }
+}
+
+/// Opens a file to record to, appends if it already exists
+pub fn open(path: &Path) -> color_eyre::Result<fs::File> {
+    fs::File::options()
+        .create(true)
+        .append(true)
+        .open(path)
+        .wrap_err_with(|| {
+            format!("Could not open recording at: {}", path.display())
+        })
+}
+
+impl<S: BreakSource> BreakSource for Recorder<S> {
+    fn next(&mut self) -> Option<Message> {
+        let message = self.source.next()?;
+        if let Some(recorded) = SourceMessage::from_message(&message) {
+            let recorded = Recorded {
+                at: self.clock.now(),
+                message: recorded,
+            };
+            let mut line = serde_json::to_string(&recorded)
+                .expect("recorded messages always serialize");
+            line.push('\n');
+            // a broken recording should not stop the reminders
+            if let Err(e) = self.file.write_all(line.as_bytes()) {
+                eprintln!("Could not write to recording: {e}");
+            }
+        }
+        Some(message)
+    }
+}
+
+/// Reads a recording made with [`Recorder`]
+pub fn read(path: &Path) -> color_eyre::Result<Vec<Recorded>> {
+    let file = fs::File::open(path).wrap_err_with(|| {
+        format!("Could not open recording at: {}", path.display())
+    })?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|(idx, line)| {
+            let line = line.wrap_err("Could not read recording")?;
+            serde_json::from_str(&line).wrap_err_with(|| {
+                format!("Invalid message on line {} of recording", idx + 1)
+            })
+        })
+        .collect()
+}
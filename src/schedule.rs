@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+
+use jiff::civil::{Date, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::window::Window;
+
+/// Changes to the window and activities for specific weekdays or dates.
+/// Days that are not mentioned use the planners window and all activities.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Schedule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monday: Option<Day>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tuesday: Option<Day>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wednesday: Option<Day>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thursday: Option<Day>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub friday: Option<Day>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saturday: Option<Day>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sunday: Option<Day>,
+    /// these take precedence over the weekdays
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub date: BTreeMap<Date, Day>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Day {
+    /// set to false to have no reminders at all on this day
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    /// replaces the planners window on this day
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<Window>,
+    /// descriptions of the activities to issue on this day, if left out
+    /// all activities are issued
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activities: Option<Vec<String>>,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl Default for Day {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: None,
+            activities: None,
+        }
+    }
+}
+
+impl Schedule {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub fn day(&self, date: Date) -> Option<&Day> {
+        if let Some(day) = self.date.get(&date) {
+            return Some(day);
+        }
+
+        match date.weekday() {
+            Weekday::Monday => self.monday.as_ref(),
+            Weekday::Tuesday => self.tuesday.as_ref(),
+            Weekday::Wednesday => self.wednesday.as_ref(),
+            Weekday::Thursday => self.thursday.as_ref(),
+            Weekday::Friday => self.friday.as_ref(),
+            Weekday::Saturday => self.saturday.as_ref(),
+            Weekday::Sunday => self.sunday.as_ref(),
+        }
+    }
+
+    pub fn enabled_on(&self, date: Date) -> bool {
+        self.day(date).is_none_or(|day| day.enabled)
+    }
+
+    pub fn window_on<'a>(
+        &'a self,
+        date: Date,
+        default: &'a Window,
+    ) -> &'a Window {
+        self.day(date)
+            .and_then(|day| day.window.as_ref())
+            .unwrap_or(default)
+    }
+
+    pub fn includes(&self, date: Date, description: &str) -> bool {
+        self.day(date)
+            .and_then(|day| day.activities.as_ref())
+            .is_none_or(|activities| {
+                activities.iter().any(|a| a == description)
+            })
+    }
+}
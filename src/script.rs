@@ -0,0 +1,113 @@
+//! Break events read from a file or stdin, one per line:
+//!
+//! ```text
+//! # work 25 minutes then break for 5
+//! parameter-change 25m 5m
+//! sleep 25m
+//! break-started
+//! sleep 5m
+//! break-ended
+//! ```
+//!
+//! Other events are `went-idle`, `reset` and `long-reset`.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use color_eyre::eyre::Context;
+
+use crate::source::BreakSource;
+use crate::Message;
+
+pub struct Script {
+    lines: io::Lines<Box<dyn BufRead + Send>>,
+    line_number: usize,
+}
+
+enum Step {
+    Sleep(Duration),
+    Yield(Message),
+}
+
+impl Script {
+    pub fn new(reader: impl BufRead + Send + 'static) -> Self {
+        let reader: Box<dyn BufRead + Send> = Box::new(reader);
+        Self {
+            lines: reader.lines(),
+            line_number: 0,
+        }
+    }
+
+    /// Reads stdin if the path is `-`
+    pub fn open(path: &Path) -> color_eyre::Result<Self> {
+        if path == Path::new("-") {
+            return Ok(Self::new(BufReader::new(io::stdin())));
+        }
+        let file = fs::File::open(path).wrap_err_with(|| {
+            format!("Could not open script at: {}", path.display())
+        })?;
+        Ok(Self::new(BufReader::new(file)))
+    }
+}
+
+impl BreakSource for Script {
+    fn next(&mut self) -> Option<Message> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => {
+                    eprintln!("Could not read script: {e}");
+                    return None;
+                }
+            };
+            self.line_number += 1;
+            match parse_line(&line) {
+                Ok(None) => (),
+                Ok(Some(Step::Sleep(duration))) => thread::sleep(duration),
+                Ok(Some(Step::Yield(message))) => return Some(message),
+                Err(e) => {
+                    eprintln!(
+                        "Skipping line {} of script: {e}",
+                        self.line_number
+                    )
+                }
+            }
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Result<Option<Step>, String> {
+    let line = line.split('#').next().unwrap_or_default().trim();
+    let mut words = line.split_whitespace();
+    let Some(event) = words.next() else {
+        return Ok(None);
+    };
+    let mut duration = || -> Result<Duration, String> {
+        let word = words
+            .next()
+            .ok_or_else(|| format!("{event} needs a duration"))?;
+        let duration: jiff::SignedDuration = word
+            .parse()
+            .map_err(|e| format!("Could not parse duration {word}: {e}"))?;
+        Duration::try_from(duration)
+            .map_err(|_| "Duration can not be negative".to_owned())
+    };
+
+    let step = match event {
+        "sleep" => Step::Sleep(duration()?),
+        "parameter-change" => Step::Yield(Message::ParameterChange {
+            work_duration: duration()?,
+            break_duration: duration()?,
+        }),
+        "break-started" => Step::Yield(Message::BreakStarted),
+        "break-ended" => Step::Yield(Message::BreakEnded),
+        "went-idle" => Step::Yield(Message::WentIdle),
+        "reset" => Step::Yield(Message::Reset),
+        "long-reset" => Step::Yield(Message::LongReset),
+        other => return Err(format!("Unknown event: {other}")),
+    };
+    Ok(Some(step))
+}
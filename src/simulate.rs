@@ -0,0 +1,250 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use color_eyre::eyre::Context;
+use jiff::civil::Date;
+use jiff::Zoned;
+
+use crate::calendar;
+use crate::config::Config;
+use crate::explain::Decision;
+use crate::history::EventKind;
+use crate::record::{Recorded, SourceMessage};
+use crate::time::{Clock, SimulatedClock};
+use crate::{Planner, Store};
+
+/// A break during the simulated day and what would have been shown
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedBreak {
+    pub at: Zoned,
+    pub number: usize,
+    pub activities: Vec<String>,
+    pub decisions: Vec<Decision>,
+}
+
+impl fmt::Display for SimulatedBreak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} break #{}", self.at.strftime("%H:%M"), self.number)?;
+        if !self.activities.is_empty() {
+            write!(f, ": {}", self.activities.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the planner through the window on `date` as if the user works for
+/// `work_duration` and then takes a break of `break_duration`. Every
+/// activity shown is assumed to be done.
+///
+/// The store should be a fresh one, its history is filled with the
+/// simulated day.
+pub fn day(
+    config: Config,
+    store: Store,
+    date: Date,
+    work_duration: Duration,
+    break_duration: Duration,
+) -> color_eyre::Result<Vec<SimulatedBreak>> {
+    if !config.schedule.enabled_on(date) {
+        return Ok(Vec::new());
+    }
+
+    let window = config.schedule.window_on(date, &config.window).clone();
+    let start = date
+        .to_zoned(jiff::tz::TimeZone::system())
+        .wrap_err("Could not get start of the simulated day")?
+        .with()
+        .time(window.start())
+        .build()
+        .wrap_err("Window start does not exist on the simulated day")?;
+    let end = window
+        .occurrence(&start)
+        .pop()
+        .expect("window has at least one range")
+        .end;
+
+    let clock = SimulatedClock::starting_at(
+        start.clone(),
+        break_duration,
+        work_duration,
+    );
+    store
+        .last_check()
+        .set(&start.yesterday().wrap_err("Could not go back a day")?)
+        .wrap_err("Could not prepare store")?;
+    let planner = Planner {
+        load: config.load,
+        store,
+        activities: config.activities,
+        window: config.window,
+        schedule: config.schedule,
+        period: Some(work_duration + break_duration),
+        break_duration: Some(break_duration),
+        program_start: start,
+        clock: Arc::new(clock.clone()),
+        calendar: calendar::load_all(&config.calendars)?,
+    };
+
+    let mut breaks = Vec::new();
+    for number in 1.. {
+        clock.next_break();
+        let at = clock.now();
+        if at >= end {
+            break;
+        }
+
+        let mut activities = Vec::new();
+        let (reminders, decisions) = planner.reminder_explained(false)?;
+        for activity in reminders {
+            if activity.needs_confirm {
+                planner.mark_completed(&activity.description)?;
+            }
+            activities.push(activity.description);
+        }
+        breaks.push(SimulatedBreak {
+            at,
+            number,
+            activities,
+            decisions,
+        });
+        clock.break_ends();
+    }
+    Ok(breaks)
+}
+
+/// What replaying a recorded message led to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replayed {
+    pub at: Zoned,
+    pub occasion: Occasion,
+    pub activities: Vec<String>,
+    pub decisions: Vec<Decision>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Occasion {
+    Break,
+    /// back after being idle, long if away long enough for a long break
+    Returned {
+        long: bool,
+    },
+}
+
+impl fmt::Display for Replayed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let occasion = match self.occasion {
+            Occasion::Break => "break",
+            Occasion::Returned { long: false } => "returned",
+            Occasion::Returned { long: true } => "returned from long break",
+        };
+        write!(f, "{} {occasion}", self.at.strftime("%Y-%m-%d %H:%M"))?;
+        if !self.activities.is_empty() {
+            write!(f, ": {}", self.activities.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Feeds a recording through the planner as the ui would, with the clock
+/// set to the time each message arrived. Every activity shown is assumed
+/// to be done. Like the ui no reminders are given while paused or while
+/// the reminder window is still open, it stays open until the break ends
+/// if it shows an activity that needs no confirmation.
+///
+/// The store should be a fresh one, its history is filled with the
+/// replayed messages.
+pub fn replay(
+    config: Config,
+    store: Store,
+    recording: &[Recorded],
+) -> color_eyre::Result<Vec<Replayed>> {
+    let Some(first) = recording.first() else {
+        return Ok(Vec::new());
+    };
+
+    let clock = SimulatedClock::starting_at(
+        first.at.clone(),
+        Duration::ZERO,
+        Duration::ZERO,
+    );
+    store
+        .last_check()
+        .set(&first.at.yesterday().wrap_err("Could not go back a day")?)
+        .wrap_err("Could not prepare store")?;
+    let mut planner = Planner {
+        load: config.load,
+        store,
+        activities: config.activities,
+        window: config.window,
+        schedule: config.schedule,
+        period: None,
+        break_duration: None,
+        program_start: first.at.clone(),
+        clock: Arc::new(clock.clone()),
+        calendar: calendar::load_all(&config.calendars)?,
+    };
+
+    let mut replayed = Vec::new();
+    let mut window_open = false;
+    for Recorded { at, message } in recording {
+        clock.set(at.clone());
+        let (occasion, (reminders, decisions)) = match message {
+            SourceMessage::ParameterChange {
+                break_duration,
+                work_duration,
+            } => {
+                planner.record(EventKind::ParameterChange {
+                    break_duration: *break_duration,
+                    work_duration: *work_duration,
+                })?;
+                planner.period = Some(*break_duration + *work_duration);
+                continue;
+            }
+            SourceMessage::BreakStarted => {
+                planner.record(EventKind::BreakStarted)?;
+                if window_open || planner.pause_state()?.is_some() {
+                    continue;
+                }
+                (Occasion::Break, planner.reminder_explained(false)?)
+            }
+            SourceMessage::BreakEnded => {
+                planner.record(EventKind::BreakEnded)?;
+                window_open = false;
+                continue;
+            }
+            SourceMessage::WentIdle => {
+                planner.record(EventKind::WentIdle)?;
+                continue;
+            }
+            SourceMessage::Reset | SourceMessage::LongReset => {
+                let long = *message == SourceMessage::LongReset;
+                planner.record(EventKind::Returned { long })?;
+                if window_open || planner.pause_state()?.is_some() {
+                    continue;
+                }
+                (
+                    Occasion::Returned { long },
+                    planner.returned_explained(long, false)?,
+                )
+            }
+        };
+
+        let mut activities = Vec::new();
+        for activity in reminders {
+            if activity.needs_confirm {
+                planner.mark_completed(&activity.description)?;
+            } else {
+                window_open = true;
+            }
+            activities.push(activity.description);
+        }
+        replayed.push(Replayed {
+            at: at.clone(),
+            occasion,
+            activities,
+            decisions,
+        });
+    }
+    Ok(replayed)
+}
@@ -0,0 +1,195 @@
+//! When to hold back reminders if there are enough breaks left, for
+//! example while a video plays or during a meeting. Conditions can be
+//! combined with `all`, `any` and `not`:
+//!
+//! ```toml
+//! skip-when = { any = [
+//!     "media-playing",
+//!     { all = ["camera-in-use", { not = { visible = "screen share" } }] },
+//!     { in-calendar-event = "~/calendars/work.ics" },
+//! ] }
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use jiff::Zoned;
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::{self, Calendar};
+use crate::config::expand_home;
+use crate::window_manager::{self, VisibleWindow, WindowRule};
+
+pub mod devices;
+pub mod mpris;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkipCondition {
+    All(Vec<SkipCondition>),
+    Any(Vec<SkipCondition>),
+    Not(Box<SkipCondition>),
+    /// A visible window matches the rule, see `skip-when-visible`
+    Visible(WindowRule),
+    /// An MPRIS media player is playing
+    MediaPlaying,
+    CameraInUse,
+    MicrophoneInUse,
+    /// The current time is inside an event in this .ics file
+    InCalendarEvent(PathBuf),
+}
+
+/// Where conditions get their information from. Implemented by
+/// [`SystemSensors`], tests can provide their own.
+pub trait Sensors {
+    fn visible_windows(&mut self) -> color_eyre::Result<Vec<VisibleWindow>>;
+    fn media_playing(&mut self) -> color_eyre::Result<bool>;
+    fn camera_in_use(&mut self) -> color_eyre::Result<bool>;
+    fn microphone_in_use(&mut self) -> color_eyre::Result<bool>;
+    fn calendar(&mut self, path: &Path) -> color_eyre::Result<Calendar>;
+    fn now(&self) -> Zoned;
+}
+
+impl SkipCondition {
+    /// A condition that can not be checked, for example as there is no
+    /// D-Bus session, does not hold. The error is logged.
+    pub fn holds(&self, sensors: &mut dyn Sensors) -> bool {
+        self.evaluate(sensors).unwrap_or(false)
+    }
+
+    /// None if the outcome depends on a condition that could not be
+    /// checked. Negating an unknown leaves it unknown so `not` can not
+    /// turn a failing sensor into a reason to skip.
+    fn evaluate(&self, sensors: &mut dyn Sensors) -> Option<bool> {
+        match self {
+            SkipCondition::All(conditions) => {
+                let mut res = Some(true);
+                for condition in conditions {
+                    match condition.evaluate(sensors) {
+                        Some(false) => return Some(false),
+                        None => res = None,
+                        Some(true) => (),
+                    }
+                }
+                res
+            }
+            SkipCondition::Any(conditions) => {
+                let mut res = Some(false);
+                for condition in conditions {
+                    match condition.evaluate(sensors) {
+                        Some(true) => return Some(true),
+                        None => res = None,
+                        Some(false) => (),
+                    }
+                }
+                res
+            }
+            SkipCondition::Not(condition) => {
+                condition.evaluate(sensors).map(|holds| !holds)
+            }
+            leaf => leaf
+                .check(sensors)
+                .inspect_err(|e| {
+                    eprintln!("Could not check skip condition {leaf}: {e:?}")
+                })
+                .ok(),
+        }
+    }
+
+    fn check(&self, sensors: &mut dyn Sensors) -> color_eyre::Result<bool> {
+        Ok(match self {
+            SkipCondition::All(_)
+            | SkipCondition::Any(_)
+            | SkipCondition::Not(_) => self.holds(sensors),
+            SkipCondition::Visible(rule) => sensors
+                .visible_windows()?
+                .iter()
+                .any(|window| rule.matches(window)),
+            SkipCondition::MediaPlaying => sensors.media_playing()?,
+            SkipCondition::CameraInUse => sensors.camera_in_use()?,
+            SkipCondition::MicrophoneInUse => sensors.microphone_in_use()?,
+            SkipCondition::InCalendarEvent(path) => {
+                let now = sensors.now();
+                sensors.calendar(path)?.busy_at(&now)
+            }
+        })
+    }
+}
+
+impl fmt::Display for SkipCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter<'_>,
+                    name: &str,
+                    conditions: &[SkipCondition]| {
+            write!(f, "{name}(")?;
+            for (idx, condition) in conditions.iter().enumerate() {
+                if idx > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{condition}")?;
+            }
+            f.write_str(")")
+        };
+        match self {
+            SkipCondition::All(conditions) => list(f, "all", conditions),
+            SkipCondition::Any(conditions) => list(f, "any", conditions),
+            SkipCondition::Not(condition) => write!(f, "not({condition})"),
+            SkipCondition::Visible(rule) => write!(f, "visible({rule:?})"),
+            SkipCondition::MediaPlaying => f.write_str("media-playing"),
+            SkipCondition::CameraInUse => f.write_str("camera-in-use"),
+            SkipCondition::MicrophoneInUse => f.write_str("microphone-in-use"),
+            SkipCondition::InCalendarEvent(path) => {
+                write!(f, "in-calendar-event({})", path.display())
+            }
+        }
+    }
+}
+
+/// The real world. Every sensor is read at most once, make a new one
+/// for every evaluation.
+pub struct SystemSensors {
+    now: Zoned,
+    window_manager: window_manager::Detected,
+    windows: Option<Vec<VisibleWindow>>,
+}
+
+impl SystemSensors {
+    pub fn new(now: Zoned, window_manager: window_manager::Detected) -> Self {
+        Self {
+            now,
+            window_manager,
+            windows: None,
+        }
+    }
+}
+
+impl Sensors for SystemSensors {
+    fn visible_windows(&mut self) -> color_eyre::Result<Vec<VisibleWindow>> {
+        if let Some(windows) = &self.windows {
+            return Ok(windows.clone());
+        }
+        let windows = self.window_manager.visible_windows()?;
+        self.windows = Some(windows.clone());
+        Ok(windows)
+    }
+
+    fn media_playing(&mut self) -> color_eyre::Result<bool> {
+        mpris::playing()
+    }
+
+    fn camera_in_use(&mut self) -> color_eyre::Result<bool> {
+        devices::camera_in_use(Path::new("/proc"))
+    }
+
+    fn microphone_in_use(&mut self) -> color_eyre::Result<bool> {
+        devices::microphone_in_use(Path::new("/proc"))
+    }
+
+    fn calendar(&mut self, path: &Path) -> color_eyre::Result<Calendar> {
+        calendar::load(&expand_home(path))
+    }
+
+    fn now(&self) -> Zoned {
+        self.now.clone()
+    }
+}
@@ -0,0 +1,130 @@
+//! Whether the webcam or microphone is used by any program. Checks the
+//! device files held open by processes and the audio devices the kernel
+//! reports as running. If PipeWire is used its nodes are checked too as
+//! it can keep devices open while nothing records.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use color_eyre::eyre::Context;
+use serde::Deserialize;
+
+/// `proc` is the mount point of procfs, normally `/proc`
+pub fn camera_in_use(proc: &Path) -> color_eyre::Result<bool> {
+    Ok(video_device_open(proc)?
+        || pipewire_dump()
+            .is_some_and(|dump| pipewire_running(&dump, &["Video/Source"])))
+}
+
+/// `proc` is the mount point of procfs, normally `/proc`
+pub fn microphone_in_use(proc: &Path) -> color_eyre::Result<bool> {
+    Ok(capture_running(proc)?
+        || pipewire_dump().is_some_and(|dump| {
+            pipewire_running(&dump, &["Audio/Source", "Stream/Input/Audio"])
+        }))
+}
+
+/// Any process with a `/dev/video*` file open. Processes of other users
+/// can not be inspected and are skipped.
+pub fn video_device_open(proc: &Path) -> color_eyre::Result<bool> {
+    let processes = fs::read_dir(proc).wrap_err("Could not list processes")?;
+    for process in processes.filter_map(Result::ok) {
+        let is_pid = process
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.chars().all(|c| c.is_ascii_digit()));
+        if !is_pid {
+            continue;
+        }
+        let Ok(fds) = fs::read_dir(process.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.filter_map(Result::ok) {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            if target
+                .to_str()
+                .is_some_and(|target| target.starts_with("/dev/video"))
+            {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Any capture stream of any sound card that is running, see
+/// `/proc/asound/card*/pcm*c/sub*/status`
+pub fn capture_running(proc: &Path) -> color_eyre::Result<bool> {
+    let Ok(cards) = fs::read_dir(proc.join("asound")) else {
+        return Ok(false); // no sound support
+    };
+    for card in cards.filter_map(Result::ok) {
+        if !card.file_name().to_string_lossy().starts_with("card") {
+            continue;
+        }
+        let devices = fs::read_dir(card.path())
+            .wrap_err("Could not list sound card devices")?;
+        for device in devices.filter_map(Result::ok) {
+            let name = device.file_name();
+            let name = name.to_string_lossy();
+            if !(name.starts_with("pcm") && name.ends_with('c')) {
+                continue;
+            }
+            let streams = fs::read_dir(device.path())
+                .wrap_err("Could not list capture streams")?;
+            for stream in streams.filter_map(Result::ok) {
+                let Ok(status) =
+                    fs::read_to_string(stream.path().join("status"))
+                else {
+                    continue;
+                };
+                if status.contains("RUNNING") {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// None if PipeWire is not used
+fn pipewire_dump() -> Option<String> {
+    let output = Command::new("pw-dump").output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[derive(Deserialize)]
+struct Object {
+    #[serde(rename = "type")]
+    kind: String,
+    info: Option<Info>,
+}
+
+#[derive(Deserialize)]
+struct Info {
+    state: Option<String>,
+    #[serde(default)]
+    props: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Whether a node of one of the media classes is running in the output
+/// of `pw-dump`. Invalid output counts as nothing running.
+pub fn pipewire_running(dump: &str, media_classes: &[&str]) -> bool {
+    let Ok(objects) = serde_json::from_str::<Vec<Object>>(dump) else {
+        return false;
+    };
+    objects
+        .iter()
+        .filter(|object| object.kind == "PipeWire:Interface:Node")
+        .filter_map(|object| object.info.as_ref())
+        .filter(|info| info.state.as_deref() == Some("running"))
+        .filter_map(|info| info.props.get("media.class"))
+        .filter_map(|class| class.as_str())
+        .any(|class| media_classes.contains(&class))
+}
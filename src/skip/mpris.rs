@@ -0,0 +1,34 @@
+//! Media players announce themselves on the D-Bus session bus under
+//! `org.mpris.MediaPlayer2.<name>`.
+
+use color_eyre::eyre::Context;
+use zbus::blocking::fdo::DBusProxy;
+use zbus::blocking::{Connection, Proxy};
+
+const PREFIX: &str = "org.mpris.MediaPlayer2.";
+
+/// Whether any player reports it is playing. A player that does not
+/// answer is left out.
+pub fn playing() -> color_eyre::Result<bool> {
+    let conn = Connection::session().wrap_err("Could not connect to D-Bus")?;
+    let names = DBusProxy::new(&conn)
+        .wrap_err("Could not talk to D-Bus")?
+        .list_names()
+        .wrap_err("Could not list D-Bus names")?;
+
+    for name in names.iter().filter(|name| name.starts_with(PREFIX)) {
+        let status: Result<String, _> = Proxy::new(
+            &conn,
+            name.as_str(),
+            "/org/mpris/MediaPlayer2",
+            "org.mpris.MediaPlayer2.Player",
+        )
+        .and_then(|player| player.get_property("PlaybackStatus"));
+        match status {
+            Ok(status) if status == "Playing" => return Ok(true),
+            Ok(_) => (),
+            Err(e) => eprintln!("Could not get status of {name}: {e}"),
+        }
+    }
+    Ok(false)
+}
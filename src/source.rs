@@ -0,0 +1,128 @@
+//! Where break events come from. Every source runs on its own thread, the
+//! messages it yields are passed to the ui.
+
+use std::thread;
+use std::time::Duration;
+
+use break_enforcer::StateUpdate;
+use iced::futures::channel::mpsc;
+
+use crate::cli::TestArgs;
+use crate::time::SimulatedClock;
+use crate::{ui, Message};
+
+/// Tells when breaks start and end, mostly by yielding
+/// [`Message::ParameterChange`], [`Message::BreakStarted`] and
+/// [`Message::BreakEnded`].
+pub trait BreakSource {
+    /// Blocks until there is a message. None if there will be no more.
+    fn next(&mut self) -> Option<Message>;
+}
+
+/// Runs the source made by `make` on a new thread. Returns a sender that
+/// can be used to add more messages for the ui.
+///
+/// The source is made on the thread as not every source can be moved
+/// between threads.
+pub fn spawn<S: BreakSource>(
+    make: impl FnOnce() -> S + Send + 'static,
+) -> mpsc::Sender<Message> {
+    let (mut tx, rx) = mpsc::channel(64);
+    let sender = tx.clone();
+    thread::spawn(move || {
+        let mut source = make();
+        while let Some(message) = source.next() {
+            tx.try_send(message)
+                .expect("cant lag so much that message can not be send");
+        }
+        eprintln!("break source ended, no more breaks will be shown");
+    });
+    ui::send_rx(rx);
+    sender
+}
+
+/// Listens to the break-enforcer daemon
+pub struct BreakEnforcer {
+    recv_update: Box<dyn FnMut() -> StateUpdate>,
+}
+
+impl BreakEnforcer {
+    pub fn connect() -> Self {
+        let mut api = break_enforcer::ReconnectingApi::new().subscribe();
+        Self {
+            recv_update: Box::new(move || api.recv_update()),
+        }
+    }
+}
+
+impl BreakSource for BreakEnforcer {
+    fn next(&mut self) -> Option<Message> {
+        Some(match (self.recv_update)() {
+            StateUpdate::ParameterChange {
+                break_duration,
+                work_duration,
+            } => Message::ParameterChange {
+                break_duration,
+                work_duration,
+            },
+            StateUpdate::BreakStarted => Message::BreakStarted,
+            StateUpdate::BreakEnded => Message::BreakEnded,
+            StateUpdate::WentIdle => Message::WentIdle,
+            StateUpdate::Reset => Message::Reset,
+            StateUpdate::LongReset => Message::LongReset,
+        })
+    }
+}
+
+/// Sends a break every second while moving the clock forward as if a
+/// whole work period passed.
+pub struct Mock {
+    test_config: TestArgs,
+    clock: SimulatedClock,
+    step: MockStep,
+}
+
+enum MockStep {
+    Parameters,
+    BreakStart(usize),
+    BreakEnd(usize),
+}
+
+impl Mock {
+    pub fn new(test_config: TestArgs, clock: SimulatedClock) -> Self {
+        Self {
+            test_config,
+            clock,
+            step: MockStep::Parameters,
+        }
+    }
+}
+
+impl BreakSource for Mock {
+    fn next(&mut self) -> Option<Message> {
+        match self.step {
+            MockStep::Parameters => {
+                self.step = MockStep::BreakStart(0);
+                Some(Message::ParameterChange {
+                    break_duration: self.test_config.break_duration,
+                    work_duration: self.test_config.work_duration,
+                })
+            }
+            MockStep::BreakStart(i) if i >= self.test_config.periods => None,
+            MockStep::BreakStart(i) => {
+                let pause = if i == 0 { 250 } else { 1000 };
+                thread::sleep(Duration::from_millis(pause));
+                eprintln!("sending break start {i}");
+                self.step = MockStep::BreakEnd(i);
+                Some(Message::BreakStarted)
+            }
+            MockStep::BreakEnd(i) => {
+                thread::sleep(Duration::from_secs(1));
+                self.clock.break_ends();
+                self.clock.next_break();
+                self.step = MockStep::BreakStart(i + 1);
+                Some(Message::BreakEnded)
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! A work/break timer for when break-enforcer is not running. If
+//! swayidle is installed it is used to notice the user walking away,
+//! being away longer than a break restarts the work period.
+
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::mpsc as std_mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::source::BreakSource;
+use crate::Message;
+
+/// swayidle reports the user idle after this long without input
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// What swayidle reports
+pub enum Idle {
+    Started,
+    Stopped,
+}
+
+pub struct Standalone {
+    work_duration: Duration,
+    break_duration: Duration,
+    /// None if swayidle is not available
+    idle: Option<std_mpsc::Receiver<Idle>>,
+    parameters_sent: bool,
+    work_started: Instant,
+    in_break: bool,
+    went_idle: Option<Instant>,
+}
+
+impl Standalone {
+    pub fn new(work_duration: Duration, break_duration: Duration) -> Self {
+        Self::with_idle(
+            work_duration,
+            break_duration,
+            Some(spawn_idle_watcher()),
+        )
+    }
+
+    /// Takes idle events from `idle` instead of starting swayidle
+    pub fn with_idle(
+        work_duration: Duration,
+        break_duration: Duration,
+        idle: Option<std_mpsc::Receiver<Idle>>,
+    ) -> Self {
+        Self {
+            work_duration,
+            break_duration,
+            idle,
+            parameters_sent: false,
+            work_started: Instant::now(),
+            in_break: false,
+            went_idle: None,
+        }
+    }
+
+    /// Waits for the user to come back, None if they were not away long
+    /// enough for it to count as a break.
+    fn wait_for_return(&mut self, went_idle: Instant) -> Option<Message> {
+        if let Some(idle) = &self.idle {
+            while !matches!(idle.recv(), Ok(Idle::Stopped) | Err(_)) {}
+        }
+        let away = went_idle.elapsed() + IDLE_TIMEOUT;
+        if away >= self.work_duration {
+            self.work_started = Instant::now();
+            Some(Message::LongReset)
+        } else if away >= self.break_duration {
+            self.work_started = Instant::now();
+            Some(Message::Reset)
+        } else {
+            None
+        }
+    }
+
+    /// The user walking away during a break is expected, what swayidle
+    /// reported meanwhile should not count against the next work period.
+    fn ignore_idle_during_break(&mut self) {
+        let Some(idle) = &self.idle else {
+            return;
+        };
+        loop {
+            match idle.try_recv() {
+                Ok(_) => (),
+                Err(std_mpsc::TryRecvError::Empty) => return,
+                Err(std_mpsc::TryRecvError::Disconnected) => {
+                    self.idle = None;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl BreakSource for Standalone {
+    fn next(&mut self) -> Option<Message> {
+        if !self.parameters_sent {
+            self.parameters_sent = true;
+            return Some(Message::ParameterChange {
+                break_duration: self.break_duration,
+                work_duration: self.work_duration,
+            });
+        }
+
+        if self.in_break {
+            thread::sleep(self.break_duration);
+            self.ignore_idle_during_break();
+            self.in_break = false;
+            self.work_started = Instant::now();
+            return Some(Message::BreakEnded);
+        }
+
+        loop {
+            if let Some(went_idle) = self.went_idle.take() {
+                if let Some(message) = self.wait_for_return(went_idle) {
+                    return Some(message);
+                }
+            }
+
+            let until_break = self
+                .work_duration
+                .saturating_sub(self.work_started.elapsed());
+            let Some(idle) = &self.idle else {
+                thread::sleep(until_break);
+                self.in_break = true;
+                return Some(Message::BreakStarted);
+            };
+            match idle.recv_timeout(until_break) {
+                Err(std_mpsc::RecvTimeoutError::Timeout) => {
+                    self.in_break = true;
+                    return Some(Message::BreakStarted);
+                }
+                Ok(Idle::Started) => {
+                    self.went_idle = Some(Instant::now());
+                    return Some(Message::WentIdle);
+                }
+                Ok(Idle::Stopped) => (),
+                Err(std_mpsc::RecvTimeoutError::Disconnected) => {
+                    // only time the breaks from now on
+                    self.idle = None;
+                }
+            }
+        }
+    }
+}
+
+/// The sender is dropped if swayidle can not be started or quits
+fn spawn_idle_watcher() -> std_mpsc::Receiver<Idle> {
+    let (tx, rx) = std_mpsc::channel();
+    let timeout = IDLE_TIMEOUT.as_secs().to_string();
+    let child = Command::new("swayidle")
+        .args(["-w", "timeout", &timeout, "echo idle"])
+        .args(["resume", "echo active"])
+        .stdout(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Could not start swayidle, no idle detection: {e}");
+            return rx;
+        }
+    };
+
+    let stdout = child.stdout.take().expect("stdout is piped");
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let event = match line.as_deref() {
+                Ok("idle") => Idle::Started,
+                Ok("active") => Idle::Stopped,
+                Ok(other) => {
+                    eprintln!("Unexpected output from swayidle: {other}");
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("Could not read from swayidle: {e}");
+                    break;
+                }
+            };
+            if tx.send(event).is_err() {
+                break;
+            }
+        }
+        let _ = child.kill();
+    });
+    rx
+}
@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::history::{Event, EventKind};
+
+/// Summary of what happened to the activities over some period
+#[derive(Debug, Default, Serialize)]
+pub struct Stats {
+    pub activities: Vec<ActivityStats>,
+    pub breaks: usize,
+    /// breaks during which no activity was shown
+    pub breaks_without_reminder: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ActivityStats {
+    pub activity: String,
+    pub shown: usize,
+    pub done: usize,
+    pub skipped: usize,
+    pub snoozed: usize,
+    /// shown but not done, skipped or snoozed
+    pub ignored: usize,
+    /// None if it was never shown
+    pub completion_rate: Option<f32>,
+    /// None if it was never confirmed
+    pub avg_secs_to_confirm: Option<f32>,
+}
+
+/// What the user did with an activity after it was shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Ignored,
+    Confirmed,
+    /// counted as done when it was shown as it needs no confirmation
+    AutoCompleted,
+    Skipped,
+    Snoozed,
+}
+
+impl Outcome {
+    pub fn is_done(self) -> bool {
+        matches!(self, Outcome::Confirmed | Outcome::AutoCompleted)
+    }
+}
+
+/// A single time an activity was shown
+#[derive(Debug, Clone)]
+pub struct Occurrence {
+    pub activity: String,
+    pub shown_at: jiff::Zoned,
+    pub outcome: Outcome,
+    /// None if the activity was ignored
+    pub decided_at: Option<jiff::Zoned>,
+}
+
+/// Events must be in order of occurrence, the returned occurrences are
+/// in order too. A confirmation counts for the last time the activity was
+/// shown if that is still undecided, even if it came breaks later.
+pub fn occurrences(events: &[Event]) -> Vec<Occurrence> {
+    let mut res: Vec<Occurrence> = Vec::new();
+    // index of the undecided occurrences
+    let mut open: HashMap<&str, usize> = HashMap::new();
+
+    for Event { at, kind } in events {
+        let (activity, outcome) = match kind {
+            EventKind::Shown { activity } => {
+                open.insert(activity, res.len());
+                res.push(Occurrence {
+                    activity: activity.clone(),
+                    shown_at: at.clone(),
+                    outcome: Outcome::Ignored,
+                    decided_at: None,
+                });
+                continue;
+            }
+            EventKind::Confirmed { activity } => (activity, Outcome::Confirmed),
+            EventKind::AutoCompleted { activity } => {
+                (activity, Outcome::AutoCompleted)
+            }
+            EventKind::Skipped { activity, .. } => (activity, Outcome::Skipped),
+            EventKind::Snoozed { activity } => (activity, Outcome::Snoozed),
+            EventKind::BreakStarted
+            | EventKind::BreakEnded
+            | EventKind::ParameterChange { .. }
+            | EventKind::Paused { .. }
+            | EventKind::Resumed
+            | EventKind::WentIdle
+            | EventKind::Returned { .. }
+            // never shown, nothing to pair it with
+            | EventKind::Credited { .. } => continue,
+        };
+
+        let Some(&idx) = open.get(activity.as_str()) else {
+            continue;
+        };
+        res[idx].outcome = outcome;
+        res[idx].decided_at = Some(at.clone());
+        // a skip or snooze can still undo an auto completion
+        if outcome != Outcome::AutoCompleted {
+            open.remove(activity.as_str());
+        }
+    }
+    res
+}
+
+/// Events must be in order of occurrence. If `only` is set activities
+/// not containing it, ignoring case, are ignored.
+pub fn summarize(events: &[Event], only: Option<&str>) -> Stats {
+    let only = only.map(str::to_lowercase);
+    let included = |activity: &str| {
+        only.as_ref()
+            .is_none_or(|only| activity.to_lowercase().contains(only))
+    };
+
+    let mut breaks = 0;
+    let mut breaks_without_reminder = 0;
+    // during a break: whether something was shown
+    let mut current_break: Option<bool> = None;
+    for Event { kind, .. } in events {
+        match kind {
+            EventKind::BreakStarted => {
+                breaks += 1;
+                if current_break == Some(false) {
+                    breaks_without_reminder += 1;
+                }
+                current_break = Some(false);
+            }
+            EventKind::BreakEnded => {
+                if current_break == Some(false) {
+                    breaks_without_reminder += 1;
+                }
+                current_break = None;
+            }
+            EventKind::Shown { activity }
+                if included(activity) && current_break.is_some() =>
+            {
+                current_break = Some(true);
+            }
+            _ => (),
+        }
+    }
+    if current_break == Some(false) {
+        breaks_without_reminder += 1;
+    }
+
+    let mut per_activity: BTreeMap<String, (ActivityStats, Vec<Duration>)> =
+        BTreeMap::new();
+    for occurrence in occurrences(events) {
+        if !included(&occurrence.activity) {
+            continue;
+        }
+
+        let (stats, confirm_times) =
+            per_activity.entry(occurrence.activity.clone()).or_default();
+        stats.shown += 1;
+        match occurrence.outcome {
+            Outcome::Ignored => stats.ignored += 1,
+            Outcome::Confirmed | Outcome::AutoCompleted => stats.done += 1,
+            Outcome::Skipped => stats.skipped += 1,
+            Outcome::Snoozed => stats.snoozed += 1,
+        }
+        if let (Outcome::Confirmed, Some(decided_at)) =
+            (occurrence.outcome, &occurrence.decided_at)
+        {
+            confirm_times.push(
+                occurrence
+                    .shown_at
+                    .duration_until(decided_at)
+                    .unsigned_abs(),
+            );
+        }
+    }
+
+    let activities = per_activity
+        .into_iter()
+        .map(|(activity, (mut stats, confirm_times))| {
+            stats.activity = activity;
+            stats.completion_rate = (stats.shown > 0)
+                .then(|| stats.done as f32 / stats.shown as f32);
+            stats.avg_secs_to_confirm =
+                (!confirm_times.is_empty()).then(|| {
+                    confirm_times.iter().sum::<Duration>().as_secs_f32()
+                        / confirm_times.len() as f32
+                });
+            stats
+        })
+        .collect();
+
+    Stats {
+        activities,
+        breaks,
+        breaks_without_reminder,
+    }
+}
+
+/// Formats as a table for the terminal
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .activities
+            .iter()
+            .map(|stats| stats.activity.chars().count())
+            .chain(["activity".len()])
+            .max()
+            .expect("chain has at least one element");
+
+        writeln!(
+            f,
+            "{:width$}  {:>6}  {:>6}  {:>7}  {:>7}  {:>7}  {:>6}  {:>10}",
+            "activity",
+            "shown",
+            "done",
+            "skipped",
+            "snoozed",
+            "ignored",
+            "done %",
+            "to confirm",
+        )?;
+        for stats in &self.activities {
+            let rate = stats
+                .completion_rate
+                .map(|rate| format!("{:.0}%", rate * 100.0))
+                .unwrap_or_else(|| "-".to_owned());
+            let to_confirm = stats
+                .avg_secs_to_confirm
+                .map(|secs| {
+                    let secs = secs.round() as u64;
+                    format!("{}m {:02}s", secs / 60, secs % 60)
+                })
+                .unwrap_or_else(|| "-".to_owned());
+            writeln!(
+                f,
+                "{:width$}  {:>6}  {:>6}  {:>7}  {:>7}  {:>7}  {:>6}  {:>10}",
+                stats.activity,
+                stats.shown,
+                stats.done,
+                stats.skipped,
+                stats.snoozed,
+                stats.ignored,
+                rate,
+                to_confirm,
+            )?;
+        }
+        writeln!(f)?;
+        write!(
+            f,
+            "breaks: {}, without reminder: {}",
+            self.breaks, self.breaks_without_reminder
+        )
+    }
+}
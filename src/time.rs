@@ -1,69 +1,95 @@
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use jiff::civil::Time;
 
-struct TestState {
-    current: jiff::Zoned,
-    break_duration: Duration,
-    work_duration: Duration,
+/// Source of the current time for the planner. Use [`SystemClock`] for the
+/// real thing and [`SimulatedClock`] to drive time yourself.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> jiff::Zoned;
 }
 
-static TEST_STATE: Mutex<Option<TestState>> = Mutex::new(None);
-static TESTING: AtomicBool = AtomicBool::new(false);
+/// The time as reported by the operating system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
 
-pub fn setup_mock_from_args(args: &crate::cli::TestArgs) {
-    setup_mock_with(
-        args.program_start,
-        args.break_duration,
-        args.work_duration,
-    );
+impl Clock for SystemClock {
+    fn now(&self) -> jiff::Zoned {
+        jiff::Zoned::now()
+    }
 }
 
-pub fn setup_mock_with(
-    program_start: Time,
+struct SimulatedState {
+    current: jiff::Zoned,
     break_duration: Duration,
     work_duration: Duration,
-) {
-    let now = jiff::Zoned::now();
-    let program_start = now.with().time(program_start).build().unwrap();
+}
 
-    *TEST_STATE.try_lock().expect("should not yet be in use") =
-        Some(TestState {
-            current: program_start,
-            break_duration,
-            work_duration,
-        });
-    TESTING.store(true, Ordering::Relaxed);
+/// A clock that only moves when told to. Clones share the same time, keep
+/// one to advance the clock handed to a planner.
+#[derive(Clone)]
+pub struct SimulatedClock {
+    state: Arc<Mutex<SimulatedState>>,
 }
 
-pub fn zoned_now() -> jiff::Zoned {
-    if TESTING.load(Ordering::Relaxed) {
-        TEST_STATE
+impl SimulatedClock {
+    /// Starts today at `program_start`
+    pub fn new(
+        program_start: Time,
+        break_duration: Duration,
+        work_duration: Duration,
+    ) -> Self {
+        let now = jiff::Zoned::now();
+        let program_start = now.with().time(program_start).build().unwrap();
+        Self::starting_at(program_start, break_duration, work_duration)
+    }
+
+    pub fn starting_at(
+        start: jiff::Zoned,
+        break_duration: Duration,
+        work_duration: Duration,
+    ) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SimulatedState {
+                current: start,
+                break_duration,
+                work_duration,
+            })),
+        }
+    }
+
+    pub fn from_args(args: &crate::cli::TestArgs) -> Self {
+        Self::new(args.program_start, args.break_duration, args.work_duration)
+    }
+
+    /// Move forward by the work duration
+    pub fn next_break(&self) {
+        let state = &mut *self.state.lock().expect("nothing should panic");
+        state.current += state.work_duration
+    }
+
+    /// Move forward by the break duration
+    pub fn break_ends(&self) {
+        let state = &mut *self.state.lock().expect("nothing should panic");
+        state.current += state.break_duration
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.state.lock().expect("nothing should panic").current += duration
+    }
+
+    /// Jump to a given time, may be in the past
+    pub fn set(&self, at: jiff::Zoned) {
+        self.state.lock().expect("nothing should panic").current = at
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> jiff::Zoned {
+        self.state
             .lock()
             .expect("nothing should panic")
-            .as_ref()
-            .expect("setup_mock_with should have run already")
             .current
             .clone()
-    } else {
-        jiff::Zoned::now()
     }
 }
-
-pub fn next_break() {
-    let mut state = TEST_STATE.lock().expect("nothing should panic");
-    let state = state
-        .as_mut()
-        .expect("setup_mock_with should have run already");
-    state.current += state.work_duration
-}
-
-pub fn break_ends() {
-    let mut state = TEST_STATE.lock().expect("nothing should panic");
-    let state = state
-        .as_mut()
-        .expect("setup_mock_with should have run already");
-    state.current += state.break_duration
-}
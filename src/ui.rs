@@ -1,15 +1,23 @@
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::{Duration, Instant};
 
-use iced::futures::channel::mpsc;
+use color_eyre::eyre::{eyre, Context};
+use iced::futures::channel::{mpsc, oneshot};
 use iced::futures::Stream;
 use iced::widget::Container;
 use iced::Length::Fill;
 use iced::{widget, Alignment, Theme};
 use iced::{window, Element, Subscription, Task};
 
-use crate::cli::RunArgs;
-use crate::{time, window_manager, Activity, Message, Planner, Store};
+use crate::calendar::{Calendar, CalendarWatcher};
+use crate::config::{Config, ConfigWatcher};
+use crate::control::{Reply, Request, Response};
+use crate::history::EventKind;
+use crate::skip::{SkipCondition, SystemSensors};
+use crate::time::Clock;
+use crate::window_manager::{self, WindowRule};
+use crate::{Activity, Message, Planner, Store};
 
 pub struct Ui {
     planner: Planner,
@@ -17,12 +25,37 @@ pub struct Ui {
     active_theme: Theme,
     active_window: Option<window::Id>,
     active_reminders: Vec<DisplayedActivity>,
-    skip_when_visible: Vec<String>,
+    skip_when_visible: Vec<WindowRule>,
+    skip_when: Option<SkipCondition>,
+    calendars: CalendarWatcher,
+    explain: bool,
+    config_watcher: ConfigWatcher,
+    window_manager: window_manager::Detected,
+    /// Reminders waiting for the skip conditions to be checked
+    pending: Option<Pending>,
+}
+
+struct Pending {
+    occasion: Occasion,
+    /// for a trigger, answered once the reminders are known
+    reply: Option<Reply>,
+}
+
+/// Why reminders are being looked up
+#[derive(Debug, Clone, Copy)]
+enum Occasion {
+    Break,
+    Trigger,
+    /// back after being idle, a natural break
+    Returned {
+        long: bool,
+    },
 }
 
 struct DisplayedActivity {
     description: String,
     checkbox: Option<bool>,
+    skip_reason: String,
 }
 
 impl Ui {
@@ -30,13 +63,19 @@ impl Ui {
         include_bytes!("../fonts/Poppins-Medium.ttx");
 
     pub fn new(
-        RunArgs {
-            activity,
+        Config {
+            activities,
             window: deadline,
             skip_when_visible: apps_blocking_activity,
+            skip_when,
+            calendars,
             load,
-        }: RunArgs,
+            schedule,
+            explain,
+        }: Config,
+        config_watcher: ConfigWatcher,
         store: Store,
+        clock: Arc<dyn Clock>,
     ) -> (Self, Task<Message>) {
         (
             Ui {
@@ -44,14 +83,23 @@ impl Ui {
                 active_window: None,
                 active_reminders: Vec::new(),
                 skip_when_visible: apps_blocking_activity,
+                skip_when,
+                calendars: CalendarWatcher::new(calendars),
+                explain,
+                config_watcher,
+                window_manager: window_manager::Detected::default(),
+                pending: None,
                 planner: Planner {
                     store,
-                    activities: activity,
+                    activities,
                     window: deadline,
+                    schedule,
                     load,
                     period: None,
-                    program_start: time::zoned_now(),
+                    program_start: clock.now(),
                     break_duration: None,
+                    clock,
+                    calendar: Calendar::default(),
                 },
                 error: None,
             },
@@ -72,27 +120,46 @@ impl Ui {
                 break_duration,
                 work_duration,
             } => {
+                self.planner.record(EventKind::ParameterChange {
+                    break_duration: *break_duration,
+                    work_duration: *work_duration,
+                })?;
                 self.planner.period = Some(*break_duration + *work_duration);
                 Task::none()
             }
             Message::BreakStarted => {
-                if self.active_window.is_some() {
+                self.planner.record(EventKind::BreakStarted)?;
+                if self.active_window.is_some() || self.pending.is_some() {
+                    return Ok(Task::none());
+                }
+                if let Some(pause) = self.planner.pause_state()? {
+                    eprintln!("got break start while {pause}, not reminding");
                     return Ok(Task::none());
                 }
 
-                self.update_active_reminders()?;
-                self.active_theme = self.update_theme();
-
-                if self.active_reminders.is_empty() {
-                    Task::none()
-                } else {
-                    eprintln!("got break start, opening window");
-                    let (id, task) = window::open(window::Settings::default());
-                    self.active_window = Some(id);
-                    task.discard()
+                eprintln!("got break start");
+                self.open_reminders(Occasion::Break, None)
+            }
+            Message::WentIdle => {
+                self.planner.record(EventKind::WentIdle)?;
+                Task::none()
+            }
+            Message::Reset | Message::LongReset => {
+                let long = matches!(message, Message::LongReset);
+                self.planner.record(EventKind::Returned { long })?;
+                if self.active_window.is_some() || self.pending.is_some() {
+                    return Ok(Task::none());
+                }
+                if let Some(pause) = self.planner.pause_state()? {
+                    eprintln!("user returned while {pause}, not reminding");
+                    return Ok(Task::none());
                 }
+
+                eprintln!("user returned, long break: {long}");
+                self.open_reminders(Occasion::Returned { long }, None)
             }
             Message::BreakEnded => {
+                self.planner.record(EventKind::BreakEnded)?;
                 self.active_reminders = self
                     .active_reminders
                     .drain(..)
@@ -100,14 +167,7 @@ impl Ui {
                         checkbox.is_some()
                     })
                     .collect();
-
-                if let Some(id) = self.active_window {
-                    if self.active_reminders.is_empty() {
-                        self.active_window = None;
-                        return Ok(window::close(id));
-                    }
-                }
-                Task::none()
+                self.close_window_if_done()
             }
             Message::Confirmed { activity, at } => {
                 let Some(to_remove) = self
@@ -127,31 +187,285 @@ impl Ui {
 
                 self.active_reminders.swap_remove(to_remove);
                 self.planner.mark_completed(activity)?;
+                self.close_window_if_done()
+            }
+            Message::Snoozed { activity } => {
+                let Some(to_remove) = self
+                    .active_reminders
+                    .iter()
+                    .position(|act| &act.description == activity)
+                else {
+                    return Ok(Task::none());
+                };
 
-                if let Some(id) = self.active_window {
-                    if self.active_reminders.is_empty() {
-                        self.active_window = None;
-                        return Ok(window::close(id));
-                    }
+                self.active_reminders.remove(to_remove);
+                self.planner.snooze(activity)?;
+                self.close_window_if_done()
+            }
+            Message::SkipReasonChanged { activity, reason } => {
+                if let Some(displayed) = self
+                    .active_reminders
+                    .iter_mut()
+                    .find(|act| &act.description == activity)
+                {
+                    displayed.skip_reason.clone_from(reason);
                 }
                 Task::none()
             }
+            Message::Skipped { activity } => {
+                let Some(to_remove) = self
+                    .active_reminders
+                    .iter()
+                    .position(|act| &act.description == activity)
+                else {
+                    return Ok(Task::none());
+                };
+
+                let skipped = self.active_reminders.remove(to_remove);
+                let reason = Some(skipped.skip_reason.trim().to_owned())
+                    .filter(|reason| !reason.is_empty());
+                self.planner.skip(activity, reason)?;
+                self.close_window_if_done()
+            }
+            Message::Control { request, reply } => {
+                // errors go to the requester instead of a popup
+                let (response, task) =
+                    self.handle_control(request, reply).unwrap_or_else(|e| {
+                        (Some(Response::Error(format!("{e:?}"))), Task::none())
+                    });
+                if let Some(response) = response {
+                    reply.send(response);
+                }
+                task
+            }
+            Message::SkipChecked {
+                should_skip_if_reasonable,
+            } => {
+                let Some(Pending { occasion, reply }) = self.pending.take()
+                else {
+                    return Ok(Task::none());
+                };
+                let res =
+                    self.show_reminders(occasion, *should_skip_if_reasonable);
+                let Some(reply) = reply else {
+                    return res;
+                };
+                let (response, task) = match res {
+                    Ok(task) if self.active_reminders.is_empty() => (
+                        Response::Ok(
+                            "nothing to remind of right now".to_owned(),
+                        ),
+                        task,
+                    ),
+                    Ok(task) => (
+                        Response::Ok(format!(
+                            "showing {} reminder(s)",
+                            self.active_reminders.len()
+                        )),
+                        task,
+                    ),
+                    Err(e) => (Response::Error(format!("{e:?}")), Task::none()),
+                };
+                reply.send(response);
+                task
+            }
         })
     }
 
+    /// Checks the skip conditions off the event loop, the reminders are
+    /// shown once that is done. A `reply` is answered then too.
+    fn open_reminders(
+        &mut self,
+        occasion: Occasion,
+        reply: Option<Reply>,
+    ) -> Task<Message> {
+        // an invalid config must not stop the reminders, keep running on
+        // the previous one
+        match self.config_watcher.reload_if_changed() {
+            Ok(Some(config)) => self.apply_config(config),
+            Ok(None) => (),
+            Err(e) => eprintln!("{e:?}"),
+        }
+        // only shown next to the reminders it happened with
+        self.error = None;
+
+        let condition = SkipCondition::Any(
+            self.skip_when_visible
+                .iter()
+                .cloned()
+                .map(SkipCondition::Visible)
+                .chain(self.skip_when.clone())
+                .collect(),
+        );
+        let sensors = SystemSensors::new(
+            self.planner.clock.now(),
+            self.window_manager.clone(),
+        );
+        self.pending = Some(Pending { occasion, reply });
+        Task::perform(check_skip(condition, sensors), |should_skip| {
+            Message::SkipChecked {
+                should_skip_if_reasonable: should_skip,
+            }
+        })
+    }
+
+    /// Opens a window if the planner has any reminders right now
+    fn show_reminders(
+        &mut self,
+        occasion: Occasion,
+        should_skip_if_reasonable: bool,
+    ) -> color_eyre::Result<Task<Message>> {
+        self.update_active_reminders(occasion, should_skip_if_reasonable)?;
+        self.active_theme = self.update_theme();
+
+        if self.active_reminders.is_empty() {
+            Ok(Task::none())
+        } else {
+            eprintln!("opening window");
+            let (id, task) = window::open(window::Settings::default());
+            self.active_window = Some(id);
+            Ok(task.discard())
+        }
+    }
+
+    /// Without a response the reply is sent later
+    fn handle_control(
+        &mut self,
+        request: &Request,
+        reply: &Reply,
+    ) -> color_eyre::Result<(Option<Response>, Task<Message>)> {
+        Ok(match request {
+            Request::Status => {
+                let response = Response::Status(self.planner.status()?);
+                (Some(response), Task::none())
+            }
+            Request::Done { activity } => {
+                let Some(known) = self
+                    .planner
+                    .activities
+                    .iter()
+                    .find(|known| &known.description == activity)
+                else {
+                    return Err(eyre!("There is no activity: {activity}"));
+                };
+                if !self.planner.is_outstanding(known)? {
+                    return Err(eyre!(
+                        "{activity} is already done, not counting it again"
+                    ));
+                }
+                self.planner.mark_completed(activity)?;
+                self.active_reminders
+                    .retain(|displayed| &displayed.description != activity);
+                let response = Response::Ok(format!("marked {activity} done"));
+                (Some(response), self.close_window_if_done())
+            }
+            Request::Trigger => {
+                if self.active_window.is_some() {
+                    return Err(eyre!("A window is already open"));
+                }
+                if self.pending.is_some() {
+                    return Err(eyre!("Already looking up reminders"));
+                }
+                (
+                    None,
+                    self.open_reminders(Occasion::Trigger, Some(reply.clone())),
+                )
+            }
+            Request::Pause { duration } => {
+                let until = duration
+                    .map(|duration| {
+                        self.planner
+                            .clock
+                            .now()
+                            .checked_add(duration)
+                            .wrap_err("Pause ends too far in the future")
+                    })
+                    .transpose()?;
+                self.planner.pause(until)?;
+                let message = match self.planner.pause_state()? {
+                    Some(pause) => pause.to_string(),
+                    None => "pause already ended".to_owned(),
+                };
+                (Some(Response::Ok(message)), Task::none())
+            }
+            Request::Resume => {
+                self.planner.resume()?;
+                (Some(Response::Ok("resumed".to_owned())), Task::none())
+            }
+            Request::History { since } => {
+                let events = match since {
+                    Some(since) => self.planner.store.events_between(*since..),
+                    None => self.planner.store.events_between(..),
+                }?;
+                (Some(Response::History(events)), Task::none())
+            }
+        })
+    }
+
+    fn close_window_if_done(&mut self) -> Task<Message> {
+        if let Some(id) = self.active_window {
+            if self.active_reminders.is_empty() {
+                self.active_window = None;
+                return window::close(id);
+            }
+        }
+        Task::none()
+    }
+
+    /// Counts are stored per activity description. They are thus kept for
+    /// activities that are still in the new config.
+    fn apply_config(
+        &mut self,
+        Config {
+            activities,
+            window,
+            load,
+            skip_when_visible,
+            skip_when,
+            calendars,
+            schedule,
+            explain,
+        }: Config,
+    ) {
+        eprintln!("config file changed, applying new config");
+        self.planner.activities = activities;
+        self.planner.window = window;
+        self.planner.load = load;
+        self.planner.schedule = schedule;
+        self.skip_when_visible = skip_when_visible;
+        self.skip_when = skip_when;
+        self.calendars.set_paths(calendars);
+        self.explain = explain;
+    }
+
     fn update_active_reminders(
         &mut self,
+        occasion: Occasion,
+        should_skip_if_reasonable: bool,
     ) -> Result<(), color_eyre::eyre::Error> {
-        let should_skip_if_reasonable = window_manager::visible_windows()?
-            .into_iter()
-            .any(|window| {
-                self.skip_when_visible.iter().any(|app| {
-                    window.to_lowercase().contains(&app.to_lowercase())
-                })
-            });
-        self.active_reminders = self
-            .planner
-            .reminder(should_skip_if_reasonable)?
+        // the files are updated by syncing, only changed ones are read
+        self.planner.calendar = self.calendars.calendar();
+        let (reminders, decisions) = match occasion {
+            Occasion::Break => {
+                self.planner.reminder_explained(should_skip_if_reasonable)?
+            }
+            Occasion::Trigger => self
+                .planner
+                .triggered_explained(should_skip_if_reasonable)?,
+            Occasion::Returned { long } => self
+                .planner
+                .returned_explained(long, should_skip_if_reasonable)?,
+        };
+        if self.explain {
+            eprintln!(
+                "{occasion:?}, skip if reasonable: \
+                {should_skip_if_reasonable}"
+            );
+            for decision in decisions {
+                eprintln!("  {decision}");
+            }
+        }
+        self.active_reminders = reminders
             .into_iter()
             .map(
                 |Activity {
@@ -161,6 +475,7 @@ impl Ui {
                  }| DisplayedActivity {
                     description,
                     checkbox: needs_confirm.then_some(false),
+                    skip_reason: String::new(),
                 },
             )
             .collect();
@@ -184,36 +499,56 @@ impl Ui {
     }
 
     pub fn view(&self, _: window::Id) -> Element<Message> {
-        if let Some(error) = &self.error {
-            let error = format!("{:?}", error);
-            return widget::text(error).into();
-        }
-
-        let column = widget::column(self.active_reminders.iter().map(
+        let error = self
+            .error
+            .as_ref()
+            .map(|error| widget::text(format!("{error:?}")).into());
+        let reminders = self.active_reminders.iter().map(
             |DisplayedActivity {
                  description,
                  checkbox: needs_confirm,
+                 skip_reason,
              }| {
-                if let Some(checked) = needs_confirm {
-                    widget::checkbox(description.clone(), *checked)
-                        .text_size(80)
-                        .size(80)
-                        .on_toggle(|_| Message::Confirmed {
-                            activity: description.clone(),
-                            at: Instant::now(),
-                        })
-                        .into()
-                } else {
-                    widget::text(description)
-                        .size(80)
-                        .align_x(Alignment::Center)
-                        .into()
-                }
+                let activity: Element<Message> =
+                    if let Some(checked) = needs_confirm {
+                        widget::checkbox(description.clone(), *checked)
+                            .text_size(80)
+                            .size(80)
+                            .on_toggle(|_| Message::Confirmed {
+                                activity: description.clone(),
+                                at: Instant::now(),
+                            })
+                            .into()
+                    } else {
+                        widget::text(description)
+                            .size(80)
+                            .align_x(Alignment::Center)
+                            .into()
+                    };
+                let snooze = widget::button(widget::text("snooze").size(30))
+                    .on_press(Message::Snoozed {
+                        activity: description.clone(),
+                    });
+                let reason = widget::text_input("reason", skip_reason)
+                    .on_input(|reason| Message::SkipReasonChanged {
+                        activity: description.clone(),
+                        reason,
+                    })
+                    .width(200);
+                let skip = widget::button(widget::text("skip").size(30))
+                    .on_press(Message::Skipped {
+                        activity: description.clone(),
+                    });
+                widget::row![activity, snooze, reason, skip]
+                    .spacing(40)
+                    .align_y(Alignment::Center)
+                    .into()
             },
-        ))
-        .spacing(40)
-        .align_x(Alignment::Center)
-        .width(Fill);
+        );
+        let column = widget::column(error.into_iter().chain(reminders))
+            .spacing(40)
+            .align_x(Alignment::Center)
+            .width(Fill);
 
         Container::new(column).center(Fill).into()
     }
@@ -243,6 +578,29 @@ impl Ui {
     }
 }
 
+/// Sensors can block for a while, for example on D-Bus, so they are
+/// read on a thread of their own. One that never answers must not stop
+/// the reminders, after a timeout the conditions do not hold.
+async fn check_skip(
+    condition: SkipCondition,
+    mut sensors: SystemSensors,
+) -> bool {
+    // well within the time a control request waits for its answer
+    const TIMEOUT: Duration = Duration::from_secs(3);
+    let (tx, rx) = oneshot::channel();
+    thread::spawn(move || {
+        // the ui might have exited or stopped waiting
+        let _ = tx.send(condition.holds(&mut sensors));
+    });
+    match tokio::time::timeout(TIMEOUT, rx).await {
+        Ok(res) => res.unwrap_or(false),
+        Err(_) => {
+            eprintln!("Checking the skip conditions timed out, not skipping");
+            false
+        }
+    }
+}
+
 async fn resend_later(msg: Message, delay: Duration) -> Message {
     tokio::time::sleep(delay).await;
     msg
@@ -0,0 +1,265 @@
+use core::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+use std::time::Duration;
+
+use itertools::Itertools;
+use jiff::civil::Time;
+use jiff::Zoned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// One or more time ranges during the day in which reminders can be
+/// issued, for example: `09:00..12:00,13:00..17:30`. Each range starts
+/// after the previous one ended. Ranges may run past midnight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Window {
+    ranges: Vec<Range<Time>>,
+}
+
+impl From<Range<Time>> for Window {
+    fn from(range: Range<Time>) -> Self {
+        Self {
+            ranges: vec![range],
+        }
+    }
+}
+
+impl Window {
+    pub fn ranges(&self) -> &[Range<Time>] {
+        &self.ranges
+    }
+
+    pub fn start(&self) -> Time {
+        self.ranges[0].start
+    }
+
+    pub fn contains(&self, time: Time) -> bool {
+        self.ranges.iter().any(|range| {
+            if range.start < range.end {
+                // like 12:00..18:00
+                range.contains(&time)
+            } else {
+                // like 23:00..01:00
+                (range.start..Time::midnight()).contains(&time)
+                    || (Time::midnight()..range.end).contains(&time)
+            }
+        })
+    }
+
+    /// The time the window ends the day after it started. None if it
+    /// does not run past midnight.
+    pub fn overnight_end(&self) -> Option<Time> {
+        let crosses_midnight = self
+            .ranges
+            .iter()
+            .flat_map(|range| [range.start, range.end])
+            .tuple_windows()
+            .any(|(a, b)| b < a);
+        crosses_midnight.then(|| self.ranges.last().expect("not empty").end)
+    }
+
+    /// The most recent start of the window at or before `now`
+    pub fn start_before(&self, now: &Zoned) -> Zoned {
+        let start = at(now, self.start());
+        if start <= *now {
+            start
+        } else {
+            start.yesterday().unwrap_or_else(|_| {
+                panic!("time: {} does not exist yesterday", self.start())
+            })
+        }
+    }
+
+    /// The ranges of the window that started most recently before `now`.
+    pub fn occurrence(&self, now: &Zoned) -> Vec<Range<Zoned>> {
+        let mut cursor = self.start_before(now);
+        let mut res = Vec::new();
+        for range in &self.ranges {
+            let start = next_at(&cursor, range.start);
+            let end = next_at(&start, range.end);
+            cursor = end.clone();
+            res.push(start..end);
+        }
+        res
+    }
+
+    /// Time between `a` and `b` that falls within the window that started
+    /// most recently before `now`. The order of `a` and `b` does not matter.
+    pub fn duration_within(
+        &self,
+        a: &Zoned,
+        b: &Zoned,
+        now: &Zoned,
+    ) -> Duration {
+        let (from, to) = if a <= b { (a, b) } else { (b, a) };
+        self.occurrence(now)
+            .into_iter()
+            .map(|range| {
+                let start = (&range.start).max(from);
+                let end = (&range.end).min(to);
+                if start < end {
+                    start.duration_until(end).unsigned_abs()
+                } else {
+                    Duration::ZERO
+                }
+            })
+            .sum()
+    }
+
+    /// Time within the window that started most recently before `now`
+    /// that is left after `reference`.
+    pub fn remaining_after(&self, reference: &Zoned, now: &Zoned) -> Duration {
+        let end = self
+            .occurrence(now)
+            .pop()
+            .expect("window has at least one range")
+            .end;
+        if *reference >= end {
+            return Duration::ZERO;
+        }
+        self.duration_within(reference, &end, now)
+    }
+}
+
+/// `time` on the same day as `day`
+fn at(day: &Zoned, time: Time) -> Zoned {
+    day.with()
+        .time(time)
+        .build()
+        .unwrap_or_else(|_| panic!("time: {time} does not exist today"))
+}
+
+/// The first moment at or after `after` the clock reads `time`
+fn next_at(after: &Zoned, time: Time) -> Zoned {
+    let candidate = at(after, time);
+    if candidate >= *after {
+        candidate
+    } else {
+        candidate
+            .tomorrow()
+            .unwrap_or_else(|_| panic!("time: {time} does not exist tomorrow"))
+    }
+}
+
+fn range_parser(s: &str) -> Result<Range<Time>, String> {
+    let range_tokens = s
+        .chars()
+        .tuple_windows()
+        .filter(|(c1, c2)| *c1 == '.' && *c2 == '.')
+        .count();
+    if range_tokens != 1 {
+        return Err("Range must contain exactly one occurence of ..".to_owned());
+    }
+
+    if let Some((start, end)) = s.split_once("..") {
+        let start = Time::strptime("%H:%M", start).map_err(|e| {
+            format!(
+                "Could not parse start time, should be \
+                in format: 12:34 (hh:mm). Parse error: {e}"
+            )
+        })?;
+        let end = Time::strptime("%H:%M", end).map_err(|e| {
+            format!(
+                "Could not parse end time, should be \
+                in format: 12:34 (hh:mm). Parse error: {e}"
+            )
+        })?;
+
+        Ok(start..end)
+    } else {
+        unreachable!("We checked that '..' occured once")
+    }
+}
+
+impl FromStr for Window {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ranges = s
+            .split(',')
+            .map(str::trim)
+            .map(range_parser)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            ranges: order(ranges)?,
+        })
+    }
+}
+
+/// Sorts the ranges by time of day, starting with the one after the
+/// longest gap. That keeps the start of a window running past midnight.
+/// Ranges may not overlap.
+fn order(mut ranges: Vec<Range<Time>>) -> Result<Vec<Range<Time>>, String> {
+    const DAY: i32 = 24 * 60 * 60;
+    let secs = |time: Time| {
+        i32::from(time.hour()) * 3600
+            + i32::from(time.minute()) * 60
+            + i32::from(time.second())
+    };
+    ranges.sort_by_key(|range| range.start);
+    let gap_before = |idx: usize| {
+        let prev = &ranges[(idx + ranges.len() - 1) % ranges.len()];
+        (secs(ranges[idx].start) - secs(prev.end)).rem_euclid(DAY)
+    };
+    let first = (0..ranges.len())
+        .max_by_key(|idx| gap_before(*idx))
+        .ok_or_else(|| "Window needs at least one range".to_owned())?;
+    ranges.rotate_left(first);
+
+    // seconds since the start of the window
+    let origin = secs(ranges[0].start);
+    let mut end = 0;
+    for (idx, range) in ranges.iter().enumerate() {
+        let start = (secs(range.start) - origin).rem_euclid(DAY);
+        if idx > 0 && start < end {
+            return Err(format!(
+                "Ranges overlap: {} and {}",
+                display(&ranges[idx - 1]),
+                display(range)
+            ));
+        }
+        end = start + (secs(range.end) - secs(range.start)).rem_euclid(DAY);
+    }
+    if ranges.len() > 1 && end > DAY {
+        return Err(format!(
+            "Ranges overlap: {} and {}",
+            display(ranges.last().expect("more than one")),
+            display(&ranges[0])
+        ));
+    }
+    Ok(ranges)
+}
+
+fn display(range: &Range<Time>) -> String {
+    format!(
+        "{}..{}",
+        range.start.strftime("%H:%M"),
+        range.end.strftime("%H:%M")
+    )
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranges = self.ranges.iter().map(display).join(",");
+        f.write_str(&ranges)
+    }
+}
+
+/// Windows are written the same as on the command line
+impl Serialize for Window {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Window {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -1,29 +1,93 @@
-use color_eyre::eyre::Context;
+//! Finding out which windows are on screen. Supports Sway and i3 through
+//! swayipc, Hyprland through its socket and any other compositor that
+//! implements wlr-foreign-toplevel-management.
+
+use std::env;
+use std::sync::{Arc, Mutex};
+
+use color_eyre::eyre::{eyre, Context};
 use color_eyre::Section;
-use swayipc::Connection;
-
-pub fn visible_windows() -> color_eyre::Result<Vec<String>> {
-    let mut conn = Connection::new()
-        .wrap_err("Could not connect to sway")
-        .note(
-        "The skip-when-visible option only works with the Sway window manager",
-    )?;
-    let mut res = Vec::new();
-
-    let root = conn
-        .get_tree()
-        .wrap_err("Error getting window tree from Sway")?;
-    let mut nodes = root.nodes;
-    while !nodes.is_empty() {
-        if let Some(node) = nodes.pop() {
-            nodes.extend(node.nodes);
-            if node.visible.is_some_and(|v| v) {
-                if let Some(name) = node.name {
-                    res.push(name)
-                }
-            }
+
+pub mod hyprland;
+pub mod i3;
+pub mod rule;
+pub mod wlr;
+
+pub use hyprland::Hyprland;
+pub use i3::I3Ipc;
+pub use rule::WindowRule;
+pub use wlr::Wlr;
+
+/// A window the user can see
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VisibleWindow {
+    pub title: String,
+    /// The wayland app_id or for X11 windows the class
+    pub app_id: Option<String>,
+    pub focused: bool,
+    pub fullscreen: bool,
+    /// None if the window manager has no workspaces
+    pub workspace: Option<String>,
+}
+
+pub trait WindowManager {
+    fn visible_windows(&mut self) -> color_eyre::Result<Vec<VisibleWindow>>;
+}
+
+/// Picks the window manager to talk to from the environment variables
+/// they set. Prefers the native protocols over wlr-foreign-toplevel as
+/// those know which workspaces are visible.
+pub fn detect() -> color_eyre::Result<Box<dyn WindowManager + Send>> {
+    if env::var_os("SWAYSOCK").is_some() {
+        return Ok(Box::new(I3Ipc::sway()?));
+    }
+    if let Some(signature) = env::var_os("HYPRLAND_INSTANCE_SIGNATURE") {
+        return Ok(Box::new(Hyprland::from_signature(&signature)));
+    }
+    if env::var_os("I3SOCK").is_some() || env::var_os("DISPLAY").is_some() {
+        // DISPLAY is also set by Xwayland, only use i3 if it answers
+        if let Ok(i3) = I3Ipc::i3() {
+            return Ok(Box::new(i3));
         }
     }
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        let wlr = Wlr::connect().wrap_err("Could not connect to wayland")?;
+        return Ok(Box::new(wlr));
+    }
+    Err(eyre!("No supported window manager found")).note(
+        "The skip-when-visible option works with Sway, i3, Hyprland and \
+        compositors implementing wlr-foreign-toplevel-management",
+    )
+}
 
-    Ok(res)
+/// All the windows that are on screen
+pub fn visible_windows() -> color_eyre::Result<Vec<VisibleWindow>> {
+    detect()?.visible_windows()
+}
+
+/// The window manager found by [`detect`], kept between checks. Clones
+/// share it. Detection runs again only after talking to it failed, for
+/// example because the user switched window managers.
+#[derive(Clone, Default)]
+pub struct Detected(Arc<Mutex<Option<Box<dyn WindowManager + Send>>>>);
+
+impl Detected {
+    /// All the windows that are on screen
+    pub fn visible_windows(&self) -> color_eyre::Result<Vec<VisibleWindow>> {
+        // taken out while in use so a window manager that never answers
+        // does not block later checks, those detect a new one instead
+        let taken = self.0.lock().expect("never panics with lock held").take();
+        let mut window_manager = match taken {
+            Some(window_manager) => window_manager,
+            None => detect()?,
+        };
+        let res = window_manager.visible_windows();
+        if res.is_ok() {
+            self.0
+                .lock()
+                .expect("never panics with lock held")
+                .get_or_insert(window_manager);
+        }
+        res
+    }
 }
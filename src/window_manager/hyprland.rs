@@ -0,0 +1,137 @@
+//! Hyprland answers a single command per connection on its socket,
+//! prefixing the command with `j/` makes it answer in json.
+
+use std::collections::HashSet;
+use std::env;
+use std::ffi::OsStr;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use color_eyre::eyre::Context;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use super::{VisibleWindow, WindowManager};
+
+pub struct Hyprland {
+    socket: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct WorkspaceRef {
+    id: i64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Monitor {
+    active_workspace: WorkspaceRef,
+    /// id is 0 when no special workspace is shown
+    special_workspace: Option<WorkspaceRef>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Client {
+    mapped: bool,
+    hidden: bool,
+    workspace: WorkspaceRef,
+    title: String,
+    class: String,
+    fullscreen: Fullscreen,
+    /// 0 for the focused window, missing in older versions
+    #[serde(rename = "focusHistoryID")]
+    focus_history_id: Option<i64>,
+}
+
+/// Older versions use a bool, newer ones the fullscreen mode
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Fullscreen {
+    Bool(bool),
+    Mode(u8),
+}
+
+impl Fullscreen {
+    fn is_fullscreen(&self) -> bool {
+        match self {
+            Fullscreen::Bool(fullscreen) => *fullscreen,
+            Fullscreen::Mode(mode) => *mode != 0,
+        }
+    }
+}
+
+impl Hyprland {
+    pub fn new(socket: PathBuf) -> Self {
+        Self { socket }
+    }
+
+    /// Older versions of Hyprland keep their socket in /tmp
+    pub fn from_signature(signature: &OsStr) -> Self {
+        let runtime_dir = env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(env::temp_dir);
+        let in_dir =
+            |dir: &Path| dir.join("hypr").join(signature).join(".socket.sock");
+        let socket = in_dir(&runtime_dir);
+        let legacy = in_dir(Path::new("/tmp"));
+        if !socket.exists() && legacy.exists() {
+            return Self::new(legacy);
+        }
+        Self { socket }
+    }
+
+    fn request<T: DeserializeOwned>(
+        &self,
+        command: &str,
+    ) -> color_eyre::Result<T> {
+        let mut stream = UnixStream::connect(&self.socket)
+            .wrap_err("Could not connect to Hyprland")?;
+        stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+        stream
+            .write_all(format!("j/{command}").as_bytes())
+            .wrap_err("Could not send request")?;
+
+        let mut reply = Vec::new();
+        stream
+            .read_to_end(&mut reply)
+            .wrap_err("Could not read reply")?;
+        serde_json::from_slice(&reply).wrap_err("Invalid reply")
+    }
+}
+
+impl WindowManager for Hyprland {
+    fn visible_windows(&mut self) -> color_eyre::Result<Vec<VisibleWindow>> {
+        let monitors: Vec<Monitor> = self
+            .request("monitors")
+            .wrap_err("Error getting monitors from Hyprland")?;
+        let shown: HashSet<i64> = monitors
+            .into_iter()
+            .flat_map(|monitor| {
+                [Some(monitor.active_workspace), monitor.special_workspace]
+            })
+            .flatten()
+            .map(|workspace| workspace.id)
+            .filter(|id| *id != 0)
+            .collect();
+        let clients: Vec<Client> = self
+            .request("clients")
+            .wrap_err("Error getting windows from Hyprland")?;
+
+        Ok(clients
+            .into_iter()
+            .filter(|client| client.mapped && !client.hidden)
+            .filter(|client| shown.contains(&client.workspace.id))
+            .map(|client| VisibleWindow {
+                title: client.title,
+                app_id: Some(client.class),
+                focused: client.focus_history_id == Some(0),
+                fullscreen: client.fullscreen.is_fullscreen(),
+                workspace: Some(client.workspace.name),
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,102 @@
+//! Sway and i3 speak the same IPC protocol, we talk to them through
+//! swayipc.
+
+use std::collections::HashSet;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+use color_eyre::eyre::Context;
+use swayipc::{Connection, Node, NodeType};
+
+use super::{VisibleWindow, WindowManager};
+
+pub struct I3Ipc {
+    conn: Connection,
+    /// for error messages
+    name: &'static str,
+}
+
+impl I3Ipc {
+    /// Uses `SWAYSOCK`
+    pub fn sway() -> color_eyre::Result<Self> {
+        Self::connect("Sway", None)
+    }
+
+    /// Uses `I3SOCK` or asks i3 where its socket is
+    pub fn i3() -> color_eyre::Result<Self> {
+        Self::connect("i3", None)
+    }
+
+    pub fn sway_at(socket: &Path) -> color_eyre::Result<Self> {
+        Self::connect("Sway", Some(socket))
+    }
+
+    pub fn i3_at(socket: &Path) -> color_eyre::Result<Self> {
+        Self::connect("i3", Some(socket))
+    }
+
+    fn connect(
+        name: &'static str,
+        socket: Option<&Path>,
+    ) -> color_eyre::Result<Self> {
+        let error = || format!("Could not connect to {name}");
+        let conn = match socket {
+            Some(socket) => UnixStream::connect(socket)
+                .map(Connection::from)
+                .wrap_err_with(error)?,
+            None => Connection::new().wrap_err_with(error)?,
+        };
+        Ok(Self { conn, name })
+    }
+}
+
+impl WindowManager for I3Ipc {
+    fn visible_windows(&mut self) -> color_eyre::Result<Vec<VisibleWindow>> {
+        let visible_workspaces: HashSet<_> = self
+            .conn
+            .get_workspaces()
+            .wrap_err_with(|| {
+                format!("Error getting workspaces from {}", self.name)
+            })?
+            .into_iter()
+            .filter(|workspace| workspace.visible)
+            .map(|workspace| workspace.name)
+            .collect();
+        let root = self.conn.get_tree().wrap_err_with(|| {
+            format!("Error getting window tree from {}", self.name)
+        })?;
+
+        let mut res = Vec::new();
+        // node and the name of the workspace it is on
+        let mut nodes: Vec<(Node, Option<String>)> = vec![(root, None)];
+        while let Some((node, mut workspace)) = nodes.pop() {
+            if node.node_type == NodeType::Workspace {
+                workspace = node.name.clone();
+            }
+            let on_visible = workspace
+                .as_ref()
+                .is_some_and(|name| visible_workspaces.contains(name));
+            let is_window = node.window.is_some() || node.app_id.is_some();
+            // i3 does not know if a window is hidden behind a tab
+            let visible = node.visible.unwrap_or(on_visible) && is_window;
+            if let (true, Some(title)) = (visible, node.name) {
+                res.push(VisibleWindow {
+                    title,
+                    app_id: node.app_id.or(node
+                        .window_properties
+                        .and_then(|properties| properties.class)),
+                    focused: node.focused,
+                    fullscreen: node.fullscreen_mode.is_some_and(|m| m != 0),
+                    workspace: workspace.clone(),
+                });
+            }
+            nodes.extend(
+                node.nodes
+                    .into_iter()
+                    .chain(node.floating_nodes)
+                    .map(|child| (child, workspace.clone())),
+            );
+        }
+        Ok(res)
+    }
+}
@@ -0,0 +1,140 @@
+//! Which visible windows should make reminders wait, set with
+//! `skip-when-visible`.
+
+use std::fmt;
+
+use regex::{Regex, RegexBuilder};
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::VisibleWindow;
+
+/// Either part of the title or a set of conditions that all must hold
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum WindowRule {
+    /// Case-insensitive part of the title
+    Title(String),
+    Match(WindowMatch),
+}
+
+/// Conditions left out always hold
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct WindowMatch {
+    /// Regex the title must match, case-insensitive like the other
+    /// conditions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<TitleRegex>,
+    /// Wayland app_id or X11 class, case-insensitive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    /// Only if the window has focus
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub focused: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub fullscreen: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// By hand instead of untagged so a misspelled key or invalid regex is
+/// reported as such rather than as matching no variant
+impl<'de> Deserialize<'de> for WindowRule {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct RuleVisitor;
+
+        impl<'de> Visitor<'de> for RuleVisitor {
+            type Value = WindowRule;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("part of a window title or a table of conditions")
+            }
+
+            fn visit_str<E: de::Error>(
+                self,
+                v: &str,
+            ) -> Result<Self::Value, E> {
+                Ok(WindowRule::Title(v.to_owned()))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                map: A,
+            ) -> Result<Self::Value, A::Error> {
+                WindowMatch::deserialize(MapAccessDeserializer::new(map))
+                    .map(WindowRule::Match)
+            }
+        }
+
+        d.deserialize_any(RuleVisitor)
+    }
+}
+
+impl WindowRule {
+    pub fn matches(&self, window: &VisibleWindow) -> bool {
+        match self {
+            WindowRule::Title(part) => {
+                window.title.to_lowercase().contains(&part.to_lowercase())
+            }
+            WindowRule::Match(rule) => rule.matches(window),
+        }
+    }
+}
+
+impl WindowMatch {
+    pub fn matches(&self, window: &VisibleWindow) -> bool {
+        self.title
+            .as_ref()
+            .is_none_or(|title| title.0.is_match(&window.title))
+            && self.app_id.as_ref().is_none_or(|app_id| {
+                window
+                    .app_id
+                    .as_ref()
+                    .is_some_and(|id| id.eq_ignore_ascii_case(app_id))
+            })
+            && self.workspace.as_ref().is_none_or(|workspace| {
+                window.workspace.as_ref() == Some(workspace)
+            })
+            && (!self.focused || window.focused)
+            && (!self.fullscreen || window.fullscreen)
+    }
+}
+
+/// Compared by the pattern
+#[derive(Debug, Clone)]
+pub struct TitleRegex(pub Regex);
+
+impl PartialEq for TitleRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl fmt::Display for TitleRegex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.as_str())
+    }
+}
+
+impl Serialize for TitleRegex {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.0.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for TitleRegex {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(d)?;
+        RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .build()
+            .map(TitleRegex)
+            .map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,168 @@
+//! The wlr-foreign-toplevel-management wayland protocol, implemented by
+//! most wlroots based compositors. It does not tell which workspace a
+//! window is on, every window that is not minimized counts as visible.
+
+use std::os::unix::net::UnixStream;
+
+use color_eyre::eyre::Context;
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::wl_registry::WlRegistry;
+use wayland_client::{
+    event_created_child, Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1 as handle,
+    zwlr_foreign_toplevel_manager_v1 as manager,
+};
+
+use super::{VisibleWindow, WindowManager};
+
+pub struct Wlr {
+    conn: Connection,
+}
+
+#[derive(Default)]
+struct Toplevels {
+    toplevels: Vec<Toplevel>,
+}
+
+struct Toplevel {
+    handle: handle::ZwlrForeignToplevelHandleV1,
+    title: Option<String>,
+    app_id: Option<String>,
+    /// the states as announced by the compositor
+    states: Vec<u32>,
+}
+
+impl Toplevel {
+    fn has(&self, state: handle::State) -> bool {
+        self.states.contains(&(state as u32))
+    }
+}
+
+impl Wlr {
+    /// Uses `WAYLAND_DISPLAY`
+    pub fn connect() -> color_eyre::Result<Self> {
+        let conn = Connection::connect_to_env()
+            .wrap_err("Could not connect to the compositor")?;
+        Ok(Self { conn })
+    }
+
+    pub fn from_socket(stream: UnixStream) -> color_eyre::Result<Self> {
+        let conn = Connection::from_socket(stream)
+            .wrap_err("Could not connect to the compositor")?;
+        Ok(Self { conn })
+    }
+}
+
+impl WindowManager for Wlr {
+    fn visible_windows(&mut self) -> color_eyre::Result<Vec<VisibleWindow>> {
+        let (globals, mut queue) = registry_queue_init::<Toplevels>(&self.conn)
+            .wrap_err("Could not get wayland globals")?;
+        let manager: manager::ZwlrForeignToplevelManagerV1 =
+            globals.bind(&queue.handle(), 1..=3, ()).wrap_err(
+                "Compositor does not support \
+                wlr-foreign-toplevel-management",
+            )?;
+
+        // the first roundtrip announces the toplevels, the second makes
+        // sure their details arrived
+        let mut state = Toplevels::default();
+        for _ in 0..2 {
+            queue
+                .roundtrip(&mut state)
+                .wrap_err("Error talking to the compositor")?;
+        }
+        manager.stop();
+        for toplevel in &state.toplevels {
+            toplevel.handle.destroy();
+        }
+        self.conn
+            .flush()
+            .wrap_err("Error talking to the compositor")?;
+
+        Ok(state
+            .toplevels
+            .into_iter()
+            .filter(|toplevel| !toplevel.has(handle::State::Minimized))
+            .filter_map(|toplevel| {
+                Some(VisibleWindow {
+                    focused: toplevel.has(handle::State::Activated),
+                    fullscreen: toplevel.has(handle::State::Fullscreen),
+                    title: toplevel.title?,
+                    app_id: toplevel.app_id,
+                    workspace: None,
+                })
+            })
+            .collect())
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for Toplevels {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: <WlRegistry as Proxy>::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<manager::ZwlrForeignToplevelManagerV1, ()> for Toplevels {
+    fn event(
+        state: &mut Self,
+        _: &manager::ZwlrForeignToplevelManagerV1,
+        event: manager::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let manager::Event::Toplevel { toplevel } = event {
+            state.toplevels.push(Toplevel {
+                handle: toplevel,
+                title: None,
+                app_id: None,
+                states: Vec::new(),
+            });
+        }
+    }
+
+    event_created_child!(Toplevels, manager::ZwlrForeignToplevelManagerV1, [
+        manager::EVT_TOPLEVEL_OPCODE => (
+            handle::ZwlrForeignToplevelHandleV1,
+            ()
+        ),
+    ]);
+}
+
+impl Dispatch<handle::ZwlrForeignToplevelHandleV1, ()> for Toplevels {
+    fn event(
+        state: &mut Self,
+        proxy: &handle::ZwlrForeignToplevelHandleV1,
+        event: handle::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(toplevel) = state
+            .toplevels
+            .iter_mut()
+            .find(|toplevel| &toplevel.handle == proxy)
+        else {
+            return;
+        };
+        match event {
+            handle::Event::Title { title } => toplevel.title = Some(title),
+            handle::Event::AppId { app_id } => toplevel.app_id = Some(app_id),
+            handle::Event::State { state } => {
+                toplevel.states = state
+                    .chunks_exact(4)
+                    .map(|chunk| u32::from_ne_bytes(chunk.try_into().unwrap()))
+                    .collect();
+            }
+            _ => (),
+        }
+    }
+}
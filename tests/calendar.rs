@@ -0,0 +1,247 @@
+use std::fs;
+use std::time::{Duration, SystemTime};
+
+use break_filler::calendar::{self, Calendar, CalendarWatcher};
+use jiff::civil;
+use jiff::tz::TimeZone;
+use jiff::Zoned;
+use tempfile::tempdir;
+
+#[test]
+fn parses_events() {
+    let ics = "BEGIN:VCALENDAR\r\n\
+        VERSION:2.0\r\n\
+        BEGIN:VEVENT\r\n\
+        UID:1\r\n\
+        SUMMARY:Stand-up\\, daily\\n\\\\ notes\\Nhere\r\n\
+        DTSTART:20261019T090000Z\r\n\
+        DTEND:20261019T091500Z\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        UID:2\r\n\
+        SUMMARY:Planning with a\r\n  long title\r\n\
+        DTSTART;TZID=Europe/Amsterdam:20261019T140000\r\n\
+        DURATION:PT1H30M\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        UID:3\r\n\
+        SUMMARY:Conference\r\n\
+        DTSTART;VALUE=DATE:20261020\r\n\
+        END:VEVENT\r\n\
+        END:VCALENDAR\r\n";
+
+    let utc = |hour, minute| {
+        civil::date(2026, 10, 19)
+            .at(hour, minute, 0, 0)
+            .to_zoned(TimeZone::UTC)
+            .unwrap()
+    };
+    let week_later = utc(0, 0).checked_add(jiff::Span::new().days(7)).unwrap();
+    let events = calendar::parse(ics)
+        .unwrap()
+        .events_between(&utc(0, 0), &week_later);
+    assert_eq!(events.len(), 3);
+
+    assert_eq!(events[0].summary, "Stand-up, daily\n\\ notes\nhere");
+    assert_eq!(events[0].start, utc(9, 0));
+    assert_eq!(events[0].end, utc(9, 15));
+    assert!(events[0].contains(&utc(9, 14)));
+    assert!(!events[0].contains(&utc(9, 15)));
+
+    assert_eq!(events[1].summary, "Planning with a long title");
+    // CEST is two hours ahead of UTC
+    assert_eq!(events[1].start.timestamp(), utc(12, 0).timestamp());
+    assert_eq!(events[1].end.timestamp(), utc(13, 30).timestamp());
+
+    let start = &events[2].start;
+    assert_eq!(start.date(), civil::date(2026, 10, 20));
+    assert_eq!(events[2].end.date(), civil::date(2026, 10, 21));
+}
+
+#[test]
+fn invalid_event_is_an_error() {
+    let ics = "BEGIN:VEVENT\nSUMMARY:no start\nEND:VEVENT\n";
+    assert!(calendar::parse(ics).is_err());
+}
+
+fn utc_on(day: i8, hour: i8) -> Zoned {
+    civil::date(2026, 10, day)
+        .at(hour, 0, 0, 0)
+        .to_zoned(TimeZone::UTC)
+        .unwrap()
+}
+
+fn starts(calendar: &Calendar, from: Zoned, to: Zoned) -> Vec<String> {
+    calendar
+        .events_between(&from, &to)
+        .iter()
+        .map(|event| event.start.strftime("%d %H:%M").to_string())
+        .collect()
+}
+
+#[test]
+fn expands_recurring_events() {
+    // 2026-10-05 is a monday
+    let ics = "BEGIN:VEVENT\n\
+        UID:standup\n\
+        DTSTART:20261005T090000Z\n\
+        DTEND:20261005T091500Z\n\
+        RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH\n\
+        EXDATE:20261019T090000Z\n\
+        END:VEVENT\n\
+        BEGIN:VEVENT\n\
+        UID:standup\n\
+        RECURRENCE-ID:20261022T090000Z\n\
+        DTSTART:20261022T110000Z\n\
+        DTEND:20261022T111500Z\n\
+        END:VEVENT\n\
+        BEGIN:VEVENT\n\
+        UID:standup\n\
+        RECURRENCE-ID:20261008T090000Z\n\
+        STATUS:CANCELLED\n\
+        DTSTART:20261008T090000Z\n\
+        END:VEVENT\n\
+        BEGIN:VEVENT\n\
+        UID:retro\n\
+        DTSTART:20260130T150000Z\n\
+        DURATION:PT1H\n\
+        RRULE:FREQ=MONTHLY;BYDAY=-1FR;COUNT=12\n\
+        END:VEVENT\n\
+        BEGIN:VEVENT\n\
+        UID:lunch walk\n\
+        TRANSP:TRANSPARENT\n\
+        DTSTART:20261005T120000Z\n\
+        RRULE:FREQ=DAILY\n\
+        END:VEVENT\n";
+    let calendar = calendar::parse(ics).unwrap();
+
+    assert_eq!(
+        starts(&calendar, utc_on(1, 0), utc_on(31, 0)),
+        vec!["05 09:00", "22 11:00", "30 15:00"]
+    );
+    // the exception replaced the occurrence
+    assert!(calendar.busy_at(&utc_on(22, 11)));
+    assert!(!calendar.busy_at(&utc_on(22, 9)));
+    // the events overlapping the start are included
+    assert_eq!(
+        starts(&calendar, utc_on(30, 15), utc_on(31, 0)),
+        vec!["30 15:00"]
+    );
+}
+
+#[test]
+fn invalid_event_is_skipped() {
+    let ics = "BEGIN:VCALENDAR\n\
+        BEGIN:VEVENT\n\
+        DTSTART;TZID=W. Europe Standard Time:20261019T090000\n\
+        RRULE:FREQ=MONTHLY;BYDAY=MO;BYSETPOS=1\n\
+        END:VEVENT\n\
+        BEGIN:VEVENT\n\
+        DTSTART:20261019T130000Z\n\
+        DURATION:PT1H\n\
+        END:VEVENT\n\
+        END:VCALENDAR\n";
+    let calendar = calendar::parse(ics).unwrap();
+    assert_eq!(
+        starts(&calendar, utc_on(19, 0), utc_on(20, 0)),
+        vec!["19 13:00"]
+    );
+}
+
+#[test]
+fn daily_rule_limited_to_days_of_the_month() {
+    let ics = "BEGIN:VEVENT\n\
+        DTSTART:20260930T090000Z\n\
+        DURATION:PT1H\n\
+        RRULE:FREQ=DAILY;BYMONTHDAY=1,-1;BYMONTH=10\n\
+        END:VEVENT\n";
+    let calendar = calendar::parse(ics).unwrap();
+    let november = civil::date(2026, 11, 2)
+        .at(0, 0, 0, 0)
+        .to_zoned(TimeZone::UTC)
+        .unwrap();
+    assert_eq!(
+        starts(&calendar, utc_on(1, 0), november),
+        vec!["01 09:00", "31 09:00"]
+    );
+}
+
+#[test]
+fn unsupported_rule_is_an_error() {
+    let rule = |rule: &str| {
+        format!(
+            "BEGIN:VEVENT\nDTSTART:20261005T090000Z\nRRULE:{rule}\n\
+            END:VEVENT\n"
+        )
+    };
+    assert!(calendar::parse(&rule("FREQ=HOURLY")).is_err());
+    assert!(calendar::parse(&rule("FREQ=DAILY;BYSETPOS=1")).is_err());
+    assert!(calendar::parse(&rule("FREQ=WEEKLY;BYMONTHDAY=1")).is_err());
+}
+
+#[test]
+fn loads_directories() {
+    let dir = tempdir().unwrap();
+    let event = |start: &str| {
+        format!(
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART:{start}\n\
+            DURATION:PT30M\nEND:VEVENT\nEND:VCALENDAR\n"
+        )
+    };
+    let work = dir.path().join("work");
+    fs::create_dir(&work).unwrap();
+    fs::write(work.join("a.ics"), event("20261019T090000Z")).unwrap();
+    fs::write(work.join("b.ics"), event("20261019T130000Z")).unwrap();
+    fs::write(work.join("broken.ics"), "BEGIN:VEVENT\nEND:VEVENT\n").unwrap();
+    fs::write(work.join("notes.txt"), "not a calendar").unwrap();
+    let single = dir.path().join("single.ics");
+    fs::write(&single, event("20261019T110000Z")).unwrap();
+
+    let calendar = calendar::load_all(&[work, single]).unwrap();
+    assert_eq!(
+        starts(&calendar, utc_on(19, 0), utc_on(20, 0)),
+        vec!["19 09:00", "19 11:00", "19 13:00"]
+    );
+}
+
+#[test]
+fn watcher_rereads_changed_files() {
+    let dir = tempdir().unwrap();
+    let event = |start: &str| {
+        format!(
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART:{start}\n\
+            DURATION:PT30M\nEND:VEVENT\nEND:VCALENDAR\n"
+        )
+    };
+    let path = dir.path().join("work.ics");
+    // the mtime is set explicitly, writes can happen within its resolution
+    let write = |content: &str, modified: u64| {
+        fs::write(&path, content).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(
+                SystemTime::UNIX_EPOCH + Duration::from_secs(modified),
+            )
+            .unwrap();
+    };
+    let mut watcher = CalendarWatcher::new(vec![path.clone()]);
+
+    write(&event("20261019T090000Z"), 1);
+    let all = |watcher: &mut CalendarWatcher| {
+        starts(&watcher.calendar(), utc_on(19, 0), utc_on(20, 0))
+    };
+    assert_eq!(all(&mut watcher), vec!["19 09:00"]);
+    // unchanged mtime, not read again
+    write(&event("20261019T100000Z"), 1);
+    assert_eq!(all(&mut watcher), vec!["19 09:00"]);
+    write(&event("20261019T110000Z"), 2);
+    assert_eq!(all(&mut watcher), vec!["19 11:00"]);
+    // a broken file keeps what was read before
+    write("BEGIN:VEVENT\nEND:VEVENT\n", 3);
+    assert_eq!(all(&mut watcher), vec!["19 11:00"]);
+
+    watcher.set_paths(Vec::new());
+    assert!(all(&mut watcher).is_empty());
+}
@@ -0,0 +1,18 @@
+use std::time::Duration;
+
+use break_filler::history::{Event, EventKind};
+
+/// Events a minute apart starting now
+pub fn events(kinds: Vec<EventKind>) -> Vec<Event> {
+    let mut at = jiff::Zoned::now();
+    kinds
+        .into_iter()
+        .map(|kind| {
+            at = at.checked_add(Duration::from_secs(60)).unwrap();
+            Event {
+                at: at.clone(),
+                kind,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,292 @@
+use std::fs::{self, File};
+use std::time::{Duration, SystemTime};
+
+use break_filler::cli::{Cli, Command, ConfigArgs, RunArgs};
+use break_filler::config::{Config, ConfigWatcher};
+use break_filler::window::Window;
+use break_filler::window_manager::rule::WindowMatch;
+use break_filler::window_manager::{VisibleWindow, WindowRule};
+use clap::Parser;
+use tempfile::tempdir;
+
+fn run_args(args: &[&str]) -> RunArgs {
+    let cli =
+        Cli::try_parse_from(["break-filler", "run"].iter().chain(args.iter()))
+            .unwrap();
+    let Command::Run(run_args) = cli.command else {
+        unreachable!("we passed the run subcommand")
+    };
+    run_args
+}
+
+fn config_args(args: &[&str]) -> ConfigArgs {
+    run_args(args).config_args
+}
+
+#[test]
+fn arguments_override_file() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(
+        &path,
+        r#"
+window = "09:00..17:00"
+load = 0.5
+skip-when-visible = ["youtube"]
+
+[[activity]]
+description = "drink water"
+count = 3
+
+[[activity]]
+description = "stretch"
+needs-confirm = true
+window = "11:30..13:30"
+
+[schedule.saturday]
+enabled = false
+
+[schedule.friday]
+window = "09:00..13:00"
+activities = ["drink water"]
+
+[schedule.date.2026-12-24]
+enabled = false
+"#,
+    )
+    .unwrap();
+
+    let config = Config::resolve(&config_args(&[
+        "--config",
+        path.to_str().unwrap(),
+        "--load",
+        "0.8",
+    ]))
+    .unwrap();
+
+    assert_eq!(config.load, 0.8);
+    assert_eq!(config.window.start(), jiff::civil::time(9, 0, 0, 0));
+    assert_eq!(
+        config.skip_when_visible,
+        vec![WindowRule::Title("youtube".to_owned())]
+    );
+    assert_eq!(config.activities.len(), 2);
+    assert_eq!(config.activities[0].count, 3);
+    assert_eq!(config.activities[1].count, usize::MAX);
+    assert!(config.activities[1].needs_confirm);
+    assert_eq!(config.activities[0].window, None);
+    assert_eq!(
+        config.activities[1].window,
+        Some("11:30..13:30".parse().unwrap())
+    );
+
+    let christmas_eve = jiff::civil::date(2026, 12, 24);
+    assert!(!config.schedule.enabled_on(christmas_eve));
+    let friday = jiff::civil::date(2026, 10, 16);
+    assert!(config.schedule.includes(friday, "drink water"));
+    assert!(!config.schedule.includes(friday, "stretch"));
+}
+
+#[test]
+fn error_points_at_line() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(&path, "load = 0.5\nwindow = \"09:00-17:00\"\n").unwrap();
+
+    let err =
+        Config::resolve(&config_args(&["--config", path.to_str().unwrap()]))
+            .unwrap_err();
+    let err = format!("{err:?}");
+    assert!(err.contains("line 2"), "error was: {err}");
+}
+
+#[test]
+fn standalone_arguments_are_passed_on() {
+    let args = run_args(&["--standalone", "--work", "00:50"]);
+    assert_eq!(args.source_args(), vec!["--standalone", "--work", "00:50"]);
+    assert!(
+        Cli::try_parse_from(["break-filler", "run", "--work", "00:50"])
+            .is_err()
+    );
+    assert!(
+        Cli::try_parse_from(["break-filler", "run", "--source", "script"])
+            .is_err()
+    );
+    let other_source = Cli::try_parse_from([
+        "break-filler",
+        "run",
+        "--source",
+        "break-enforcer",
+        "--break",
+        "00:10",
+    ])
+    .unwrap();
+    assert!(other_source.check().is_err());
+    let cli = Cli::try_parse_from([
+        "break-filler",
+        "install",
+        "--source",
+        "standalone",
+        "--work",
+        "00:50",
+    ])
+    .unwrap();
+    assert!(cli.check().is_ok());
+}
+
+#[test]
+fn at_sign_without_window_is_description() {
+    let args =
+        run_args(&["--activity", "mail @bob:1", "-a", "eat:1@11:30..13:30"]);
+    assert_eq!(args.config_args.activity[0].description, "mail @bob");
+    assert_eq!(args.config_args.activity[0].count, 1);
+    assert_eq!(args.config_args.activity[0].window, None);
+    assert_eq!(args.config_args.activity[1].description, "eat");
+    assert_eq!(
+        args.config_args.activity[1].window,
+        Some("11:30..13:30".parse().unwrap())
+    );
+}
+
+#[test]
+fn simulate_takes_no_source() {
+    for args in [
+        ["simulate", "--standalone"].as_slice(),
+        &["simulate", "--work", "00:50"],
+        &["replay", "recording.jsonl", "--record", "other.jsonl"],
+        &["replay", "recording.jsonl", "--source", "script"],
+    ] {
+        let args = ["break-filler"].iter().chain(args);
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+    assert!(Cli::try_parse_from([
+        "break-filler",
+        "replay",
+        "recording.jsonl",
+        "--load",
+        "0.5"
+    ])
+    .is_ok());
+}
+
+#[test]
+fn window_rules_in_file() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(
+        &path,
+        r#"
+skip-when-visible = [
+    "mpv",
+    { title = "youtube", app-id = "firefox", fullscreen = true },
+]
+"#,
+    )
+    .unwrap();
+
+    let config =
+        Config::resolve(&config_args(&["--config", path.to_str().unwrap()]))
+            .unwrap();
+    let [WindowRule::Title(title), WindowRule::Match(rule)] =
+        config.skip_when_visible.as_slice()
+    else {
+        panic!("unexpected rules: {:?}", config.skip_when_visible)
+    };
+    assert_eq!(title, "mpv");
+    assert_eq!(rule.app_id.as_deref(), Some("firefox"));
+    assert!(rule.fullscreen);
+    assert!(!rule.focused);
+
+    let watching = VisibleWindow {
+        title: "Cats - YouTube - Mozilla Firefox".to_owned(),
+        app_id: Some("firefox".to_owned()),
+        fullscreen: true,
+        ..VisibleWindow::default()
+    };
+    // the title regex ignores case too
+    assert!(rule.matches(&watching));
+    let small = VisibleWindow {
+        fullscreen: false,
+        ..watching.clone()
+    };
+    assert!(!rule.matches(&small));
+    // conditions left out always hold
+    assert!(WindowMatch::default().matches(&small));
+}
+
+#[test]
+fn invalid_title_regex_is_reported() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(&path, "skip-when-visible = [{ title = \"(\" }]\n").unwrap();
+
+    let err =
+        Config::resolve(&config_args(&["--config", path.to_str().unwrap()]))
+            .unwrap_err();
+    assert!(format!("{err:?}").contains("regex parse error"), "{err:?}");
+}
+
+#[test]
+fn misspelled_window_rule_key_is_reported() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(&path, "skip-when-visible = [{ app-idd = \"mpv\" }]\n").unwrap();
+
+    let err =
+        Config::resolve(&config_args(&["--config", path.to_str().unwrap()]))
+            .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("unknown field `app-idd`"),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn watcher_reloads_on_change() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    let start = SystemTime::now() - Duration::from_secs(60 * 60);
+    let write = |content: &str, minutes: u64| {
+        fs::write(&path, content).unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(start + Duration::from_secs(minutes * 60))
+            .unwrap();
+    };
+
+    write("load = 0.5\n", 0);
+    let (config, mut watcher) =
+        ConfigWatcher::load(config_args(&["--config", path.to_str().unwrap()]))
+            .unwrap();
+    assert_eq!(config.load, 0.5);
+    assert!(watcher.reload_if_changed().unwrap().is_none());
+
+    write("load = 0.8\n", 1);
+    let config = watcher.reload_if_changed().unwrap().unwrap();
+    assert_eq!(config.load, 0.8);
+    assert!(watcher.reload_if_changed().unwrap().is_none());
+
+    // the caller keeps the old config, the error is only reported once
+    write("load = \"high\"\n", 2);
+    assert!(watcher.reload_if_changed().is_err());
+    assert!(watcher.reload_if_changed().unwrap().is_none());
+
+    write("load = 0.3\n", 3);
+    let config = watcher.reload_if_changed().unwrap().unwrap();
+    assert_eq!(config.load, 0.3);
+}
+
+#[test]
+fn window_ranges_are_ordered() {
+    let window: Window = "13:00..17:00,09:00..12:00".parse().unwrap();
+    assert_eq!(window.to_string(), "09:00..12:00,13:00..17:00");
+    // running past midnight keeps the evening range first
+    let window: Window = "00:30..02:00,22:00..23:00".parse().unwrap();
+    assert_eq!(window.to_string(), "22:00..23:00,00:30..02:00");
+
+    let err = "09:00..12:00,11:00..13:00".parse::<Window>().unwrap_err();
+    assert!(err.contains("overlap"), "{err}");
+    assert!("22:00..02:00,01:00..03:00".parse::<Window>().is_err());
+}
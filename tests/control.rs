@@ -0,0 +1,45 @@
+use std::os::unix::net::UnixStream;
+use std::thread;
+use std::time::Duration;
+
+use break_filler::control::{self, Request, Response};
+use break_filler::Message;
+use iced::futures::channel::mpsc;
+use tempfile::tempdir;
+
+#[test]
+fn request_reaches_ui_and_back() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("XDG_RUNTIME_DIR", dir.path());
+
+    let (tx, mut rx) = mpsc::channel(8);
+    control::spawn_server(tx).unwrap();
+    thread::spawn(move || loop {
+        match rx.try_recv() {
+            Ok(Message::Control { request, reply }) => {
+                let Request::Done { activity } = request else {
+                    panic!("unexpected request: {request:?}");
+                };
+                reply.send(Response::Ok(format!("marked {activity} done")));
+            }
+            Ok(_) => panic!("unexpected message"),
+            Err(_) => thread::sleep(Duration::from_millis(10)),
+        }
+    });
+
+    // a client that never sends a request does not block the others
+    let _silent = UnixStream::connect(control::socket_path()).unwrap();
+
+    let response = control::send(&Request::Done {
+        activity: "drink".to_owned(),
+    })
+    .unwrap();
+    let Response::Ok(message) = response else {
+        panic!("expected ok, got: {response:?}");
+    };
+    assert_eq!(message, "marked drink done");
+
+    // the socket of a running instance is not taken over
+    let (tx, _rx) = mpsc::channel(8);
+    assert!(control::spawn_server(tx).is_err());
+}
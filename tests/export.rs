@@ -0,0 +1,86 @@
+use break_filler::export;
+use break_filler::history::{Event, EventKind};
+
+use common::events;
+
+mod common;
+
+fn history() -> Vec<Event> {
+    events(vec![
+        EventKind::BreakStarted,
+        EventKind::Shown {
+            activity: "drink, tea".to_owned(),
+        },
+        EventKind::Confirmed {
+            activity: "drink, tea".to_owned(),
+        },
+        EventKind::Shown {
+            activity: "stretch".to_owned(),
+        },
+        EventKind::Skipped {
+            activity: "stretch".to_owned(),
+            reason: Some("meeting".to_owned()),
+        },
+        EventKind::BreakEnded,
+    ])
+}
+
+#[test]
+fn csv_has_row_per_event() {
+    let mut out = Vec::new();
+    export::csv(&history(), &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines.len(), 1 + 6, "header plus every event: {out}");
+    assert!(lines[5].contains("skipped,stretch,meeting"), "{}", lines[5]);
+}
+
+#[test]
+fn json_lines_has_line_per_event() {
+    let mut out = Vec::new();
+    export::json_lines(&history(), &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    for line in out.lines() {
+        serde_json::from_str::<serde_json::Value>(line).unwrap();
+    }
+    assert_eq!(out.lines().count(), 6);
+}
+
+#[test]
+fn ics_only_has_done_activities() {
+    let mut out = Vec::new();
+    export::icalendar(&history(), &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert_eq!(out.matches("BEGIN:VEVENT").count(), 1);
+    assert!(out.contains("SUMMARY:drink\\, tea\r\n"), "{out}");
+    assert!(out.contains("-drink--tea@break-filler\r\n"), "{out}");
+    assert!(!out.contains("stretch"));
+}
+
+#[test]
+fn ics_folds_and_escapes() {
+    let activity = "stretch\nthen walk \\ around the block ".repeat(3) + "é";
+    let history = events(vec![
+        EventKind::BreakStarted,
+        EventKind::Shown {
+            activity: activity.clone(),
+        },
+        EventKind::AutoCompleted { activity },
+    ]);
+    let mut out = Vec::new();
+    export::icalendar(&history, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    for line in out.split("\r\n") {
+        assert!(line.len() <= 75, "too long: {line}");
+    }
+    let unfolded = out.replace("\r\n ", "");
+    assert!(
+        unfolded.contains("SUMMARY:stretch\\nthen walk \\\\ around"),
+        "{unfolded}"
+    );
+    assert!(unfolded.contains("the block é\r\n"), "{unfolded}");
+}
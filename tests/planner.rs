@@ -1,106 +1,117 @@
-use std::sync::Mutex;
+use std::sync::Arc;
 use std::time::Duration;
 
-use break_filler::{time, Activity, Planner, Store};
+use break_filler::calendar::{Calendar, CalendarEvent};
+use break_filler::explain::Rule;
+use break_filler::history::{Event, EventKind};
+use break_filler::schedule::{Day, Schedule};
+use break_filler::time::{Clock, SimulatedClock};
+use break_filler::window::Window;
+use break_filler::{Activity, IdleAction, Planner, SkipPolicy, Store};
 use jiff::civil;
+use jiff::tz::TimeZone;
 use tempfile::tempdir;
 
-/// time mock is done via a global static, a problem when running
-/// tests in parallel. This ensures that does not happen.
-static TEST_ACTIVE: Mutex<()> = Mutex::new(());
-
-fn setup_test(test_name: &str, activity_count: usize, end_hour: i8) -> Planner {
+fn setup_test(
+    test_name: &str,
+    activity_count: usize,
+    end_hour: i8,
+) -> (Planner, SimulatedClock) {
     let path = tempdir().unwrap().path().join(test_name);
     let store = Store::new(path).unwrap();
 
     let program_start = civil::time(12, 0, 0, 0);
     let work_duration = Duration::from_secs(25 * 60);
     let break_duration = Duration::from_secs(5 * 60);
-    time::setup_mock_with(program_start, break_duration, work_duration);
+    let clock =
+        SimulatedClock::new(program_start, break_duration, work_duration);
 
-    Planner {
+    let planner = Planner {
         load: 1.0,
         store,
         activities: vec![Activity {
             description: "test".to_owned(),
             count: activity_count,
             needs_confirm: false,
+            window: None,
+            idle: IdleAction::Ignore,
+            skip: SkipPolicy::Inherit,
         }],
-        window: std::ops::Range {
-            start: civil::time(12, 0, 0, 0),
-            end: civil::time(end_hour, 0, 0, 0),
-        },
+        window: Window::from(
+            civil::time(12, 0, 0, 0)..civil::time(end_hour, 0, 0, 0),
+        ),
+        schedule: Schedule::default(),
         period: Some(work_duration + break_duration),
-        program_start: time::zoned_now(),
+        program_start: clock.now(),
         break_duration: Some(break_duration),
-    }
+        clock: Arc::new(clock.clone()),
+        calendar: Calendar::default(),
+    };
+    (planner, clock)
 }
 
 #[test]
 fn reminders2_breaks4() {
-    let _guard = TEST_ACTIVE.lock();
-    let planner = setup_test("reminders2_breaks4", 2, 14);
+    let (planner, clock) = setup_test("reminders2_breaks4", 2, 14);
 
     // `12:25 break - 12:55 break - 13:25 break - 13:55 break `
     // ` reminder                     reminder                `
 
-    time::next_break();
+    clock.next_break();
     println!("\nfirst break, should have a reminder");
     assert_ne!(planner.reminder(false).unwrap(), Vec::new());
-    time::break_ends();
+    clock.break_ends();
 
-    time::next_break();
+    clock.next_break();
     println!("\nsecond break, should have no reminder");
     assert_eq!(planner.reminder(false).unwrap(), Vec::new());
-    time::break_ends();
+    clock.break_ends();
 
-    time::next_break();
+    clock.next_break();
     println!("\nthird break, should have a reminder");
     assert_ne!(planner.reminder(false).unwrap(), Vec::new());
-    time::break_ends();
+    clock.break_ends();
 
-    time::next_break();
+    clock.next_break();
     println!("\nlast break, should have no reminder");
     assert_eq!(planner.reminder(false).unwrap(), Vec::new());
-    time::break_ends();
+    clock.break_ends();
 }
 
 #[test]
 fn reminders1_breaks4() {
-    let _guard = TEST_ACTIVE.lock();
-    let planner = setup_test("reminders1_breaks4", 1, 14);
+    let (planner, clock) = setup_test("reminders1_breaks4", 1, 14);
 
     // `12:25 break - 12:55 break - 13:25 break - 13:55 break `
     // ` reminder                     reminder                `
 
-    time::next_break();
+    clock.next_break();
     println!("\nfirst break, should have no reminder");
     assert!(planner.reminder(false).unwrap().is_empty());
-    time::break_ends();
+    clock.break_ends();
 
-    time::next_break();
+    clock.next_break();
     println!("\nsecond break, should have no reminder");
     assert!(planner.reminder(false).unwrap().is_empty());
-    time::break_ends();
+    clock.break_ends();
 
-    time::next_break();
+    clock.next_break();
     println!("\nthird break, should have reminder");
     assert!(!planner.reminder(false).unwrap().is_empty());
-    time::break_ends();
+    clock.break_ends();
 
-    time::next_break();
+    clock.next_break();
     println!("\nlast break, should have no reminder");
     assert!(planner.reminder(false).unwrap().is_empty());
-    time::break_ends();
+    clock.break_ends();
 }
 
 #[test]
 fn reminders2_breaks12() {
-    let _guard = TEST_ACTIVE.lock();
-    let planner = setup_test("reminders2_breaks12", 2, 18);
+    let (planner, clock) = setup_test("reminders2_breaks12", 2, 18);
 
     for i in 0..12 {
-        time::next_break();
+        clock.next_break();
         println!("\nbreak {i}");
         let reminders = planner.reminder(false).unwrap();
         if i == 3 || i == 7 {
@@ -108,75 +119,77 @@ fn reminders2_breaks12() {
         } else {
             assert!(reminders.is_empty(), "should be no reminders");
         }
-        time::break_ends();
+        clock.break_ends();
     }
 }
 
 #[test]
 fn reminders_inf_breaks4() {
-    let _guard = TEST_ACTIVE.lock();
-    let planner = setup_test("reminders2_breaks4", usize::MAX, 14);
+    let (planner, clock) = setup_test("reminders2_breaks4", usize::MAX, 14);
 
     // `12:25 break - 12:55 break - 13:25 break - 13:55 break `
     // ` reminder                     reminder                `
 
-    time::next_break();
+    clock.next_break();
     println!("\nfirst break, should have a reminder");
     assert_ne!(planner.reminder(false).unwrap(), Vec::new());
-    time::break_ends();
+    clock.break_ends();
 
-    time::next_break();
+    clock.next_break();
     println!("\nsecond break, should have no reminder");
     assert_ne!(planner.reminder(false).unwrap(), Vec::new());
-    time::break_ends();
+    clock.break_ends();
 
-    time::next_break();
+    clock.next_break();
     println!("\nthird break, should have a reminder");
     assert_ne!(planner.reminder(false).unwrap(), Vec::new());
-    time::break_ends();
+    clock.break_ends();
 
-    time::next_break();
+    clock.next_break();
     println!("\nlast break, should have no reminder");
     assert_ne!(planner.reminder(false).unwrap(), Vec::new());
-    time::break_ends();
+    clock.break_ends();
 }
 
 #[test]
 fn recovers() {
-    let _guard = TEST_ACTIVE.lock();
-
     let path = tempdir().unwrap().path().join("recovers");
 
     let work_duration = Duration::from_secs(25 * 60);
     let break_duration = Duration::from_secs(5 * 60);
 
-    let new_planner = |store| Planner {
+    let new_planner = |store, clock: &SimulatedClock| Planner {
         load: 1.0,
         store,
         activities: vec![Activity {
             description: "test".to_owned(),
             count: 2,
             needs_confirm: false,
+            window: None,
+            idle: IdleAction::Ignore,
+            skip: SkipPolicy::Inherit,
         }],
-        window: std::ops::Range {
-            start: civil::time(12, 0, 0, 0),
-            end: civil::time(18, 0, 0, 0),
-        },
+        window: Window::from(
+            civil::time(12, 0, 0, 0)..civil::time(18, 0, 0, 0),
+        ),
+        schedule: Schedule::default(),
         period: Some(work_duration + break_duration),
-        program_start: time::zoned_now(),
+        program_start: clock.now(),
         break_duration: Some(break_duration),
+        clock: Arc::new(clock.clone()),
+        calendar: Calendar::default(),
     };
 
     {
-        time::setup_mock_with(
+        let clock = SimulatedClock::new(
             civil::time(12, 0, 0, 0),
             break_duration,
             work_duration,
         );
         let store = Store::new(&path).unwrap();
-        let planner = new_planner(store);
+        let planner = new_planner(store, &clock);
         for i in 0..6 {
-            time::next_break();
+            clock.next_break();
             println!("\nbreak {i}");
             let reminders = planner.reminder(false).unwrap();
             if i == 3 {
@@ -184,20 +197,20 @@ fn recovers() {
             } else {
                 assert!(reminders.is_empty(), "should be no reminders");
             }
-            time::break_ends();
+            clock.break_ends();
         }
     }
 
-    time::setup_mock_with(
+    let clock = SimulatedClock::new(
         civil::time(15, 0, 0, 0),
         break_duration,
         work_duration,
     );
     let store = Store::new(&path).unwrap();
-    let planner = new_planner(store);
+    let planner = new_planner(store, &clock);
 
     for i in 6..12 {
-        time::next_break();
+        clock.next_break();
         println!("\nbreak {i}");
         let reminders = planner.reminder(false).unwrap();
         if i == 7 {
@@ -205,6 +218,494 @@ fn recovers() {
         } else {
             assert!(reminders.is_empty(), "should be no reminders");
         }
-        time::break_ends();
+        clock.break_ends();
+    }
+}
+
+#[test]
+fn activity_window() {
+    let (mut planner, clock) = setup_test("activity_window", 1, 14);
+    planner.activities[0].window =
+        Some((civil::time(12, 30, 0, 0)..civil::time(13, 30, 0, 0)).into());
+
+    // `12:25 break - 12:55 break - 13:25 break - 13:55 break `
+    // `                reminder                              `
+
+    clock.next_break();
+    println!("\nfirst break, before the activities window");
+    assert!(planner.reminder(false).unwrap().is_empty());
+    clock.break_ends();
+
+    clock.next_break();
+    println!("\nsecond break, should have a reminder");
+    assert!(!planner.reminder(false).unwrap().is_empty());
+    clock.break_ends();
+
+    clock.next_break();
+    println!("\nthird break, should have no reminder");
+    assert!(planner.reminder(false).unwrap().is_empty());
+    clock.break_ends();
+
+    clock.next_break();
+    println!("\nlast break, after the activities window");
+    assert!(planner.reminder(false).unwrap().is_empty());
+    clock.break_ends();
+}
+
+#[test]
+fn disabled_date() {
+    let (mut planner, clock) = setup_test("disabled_date", usize::MAX, 14);
+    planner.schedule.date.insert(
+        clock.now().date(),
+        Day {
+            enabled: false,
+            ..Day::default()
+        },
+    );
+
+    for i in 0..4 {
+        clock.next_break();
+        println!("\nbreak {i}");
+        assert!(planner.reminder(false).unwrap().is_empty());
+        clock.break_ends();
+    }
+}
+
+#[test]
+fn weekday_window() {
+    let (mut planner, clock) = setup_test("weekday_window", usize::MAX, 14);
+    let today = Day {
+        window: Some(
+            (civil::time(13, 0, 0, 0)..civil::time(14, 0, 0, 0)).into(),
+        ),
+        ..Day::default()
+    };
+    planner.schedule = Schedule {
+        monday: Some(today.clone()),
+        tuesday: Some(today.clone()),
+        wednesday: Some(today.clone()),
+        thursday: Some(today.clone()),
+        friday: Some(today.clone()),
+        saturday: Some(today.clone()),
+        sunday: Some(today),
+        ..Schedule::default()
+    };
+
+    // `12:25 break - 12:55 break - 13:25 break - 13:55 break `
+    // `                             reminder       reminder  `
+    for i in 0..4 {
+        clock.next_break();
+        println!("\nbreak {i}");
+        let reminders = planner.reminder(false).unwrap();
+        if i < 2 {
+            assert!(reminders.is_empty(), "should be no reminders");
+        } else {
+            assert!(!reminders.is_empty(), "should have a reminder");
+        }
+        clock.break_ends();
+    }
+}
+
+#[test]
+fn lunch_not_counted() {
+    let (mut planner, clock) = setup_test("lunch_not_counted", 2, 15);
+    planner.window = "12:00..13:00,14:00..15:00".parse().unwrap();
+
+    // `12:25 break - 12:55 break - lunch - 14:25 break - 14:55 break `
+    // ` reminder                            reminder                 `
+    let mut shown = Vec::new();
+    for i in 0..6 {
+        clock.next_break();
+        let lunch = i == 2 || i == 3;
+        if !lunch {
+            println!("\nbreak {i}");
+            shown.push(!planner.reminder(false).unwrap().is_empty());
+        }
+        clock.break_ends();
+    }
+    assert_eq!(shown, vec![true, false, true, false]);
+}
+
+#[test]
+fn snoozed_is_reissued() {
+    let (planner, clock) = setup_test("snoozed_is_reissued", 1, 14);
+
+    // `12:25 break - 12:55 break - 13:25 break - 13:55 break `
+    // `                             reminder       reminder  `
+    //                               (snoozed)
+
+    clock.next_break();
+    assert!(planner.reminder(false).unwrap().is_empty());
+    clock.break_ends();
+
+    clock.next_break();
+    assert!(planner.reminder(false).unwrap().is_empty());
+    clock.break_ends();
+
+    clock.next_break();
+    let reminders = planner.reminder(false).unwrap();
+    assert!(!reminders.is_empty());
+    planner.snooze(&reminders[0].description).unwrap();
+    clock.break_ends();
+
+    clock.next_break();
+    println!("\nlast break, should have the snoozed reminder");
+    assert!(!planner.reminder(false).unwrap().is_empty());
+    clock.break_ends();
+}
+
+#[test]
+fn snooze_restores_last_done() {
+    let (planner, clock) = setup_test("snooze_restores_last_done", 2, 14);
+
+    // `12:25 break - 12:55 break - 13:25 break - 13:55 break `
+    // ` reminder                     reminder                `
+    //                               (snoozed)
+
+    clock.next_break();
+    assert!(!planner.reminder(false).unwrap().is_empty());
+    let first_done = clock.now();
+    clock.break_ends();
+
+    clock.next_break();
+    assert!(planner.reminder(false).unwrap().is_empty());
+    clock.break_ends();
+
+    clock.next_break();
+    let reminders = planner.reminder(false).unwrap();
+    assert!(!reminders.is_empty());
+    planner.snooze(&reminders[0].description).unwrap();
+
+    let last_at = planner.store.reminder_last_at().get("test").unwrap();
+    assert_eq!(last_at, Some(first_done));
+    assert_eq!(planner.counts_for("test").unwrap().done, 1);
+}
+
+#[test]
+fn skipped_is_not_done() {
+    let (planner, clock) = setup_test("skipped_is_not_done", 2, 14);
+
+    clock.next_break();
+    let reminders = planner.reminder(false).unwrap();
+    assert!(!reminders.is_empty());
+    planner
+        .skip(&reminders[0].description, Some("busy".to_owned()))
+        .unwrap();
+    clock.break_ends();
+
+    let counts = planner.counts_for("test").unwrap();
+    assert_eq!(counts.shown, 1);
+    assert_eq!(counts.done, 0);
+    assert_eq!(counts.skipped, 1);
+
+    let mut reissued = false;
+    for _ in 0..3 {
+        clock.next_break();
+        reissued |= !planner.reminder(false).unwrap().is_empty();
+        clock.break_ends();
+    }
+    assert!(reissued, "skipped activity should be issued again");
+}
+
+#[test]
+fn history_is_kept() {
+    let (planner, clock) = setup_test("history_is_kept", usize::MAX, 14);
+
+    clock.next_break();
+    let reminders = planner.reminder(false).unwrap();
+    planner.snooze(&reminders[0].description).unwrap();
+    clock.break_ends();
+
+    let today = clock.now().date();
+    let events: Vec<_> = planner
+        .store
+        .events_between(today..=today)
+        .unwrap()
+        .into_iter()
+        .map(|event| event.kind)
+        .collect();
+    let activity = "test".to_owned();
+    assert_eq!(
+        events,
+        vec![
+            EventKind::Shown {
+                activity: activity.clone()
+            },
+            EventKind::AutoCompleted {
+                activity: activity.clone()
+            },
+            EventKind::Snoozed { activity }
+        ]
+    );
+
+    let tomorrow = today.tomorrow().unwrap();
+    assert!(planner.store.events_between(tomorrow..).unwrap().is_empty());
+}
+
+#[test]
+fn history_queried_by_date() {
+    let (planner, clock) = setup_test("history_queried_by_date", 1, 14);
+    let day = |offset: i64| {
+        clock
+            .now()
+            .date()
+            .checked_add(jiff::Span::new().days(offset))
+            .unwrap()
+    };
+    for offset in 0..5 {
+        for _ in 0..3 {
+            let at = day(offset).to_zoned(TimeZone::UTC).unwrap();
+            planner.store.record(at, EventKind::BreakStarted).unwrap();
+        }
+    }
+
+    let dates = |events: Vec<Event>| -> Vec<_> {
+        events.into_iter().map(|event| event.at.date()).collect()
+    };
+    let store = &planner.store;
+    assert_eq!(
+        dates(store.events_between(day(1)..day(2)).unwrap()),
+        [day(1); 3]
+    );
+    assert_eq!(store.events_between(day(3)..).unwrap().len(), 6);
+    assert_eq!(store.events_between(..=day(0)).unwrap().len(), 3);
+    assert_eq!(store.events_between(..).unwrap().len(), 15);
+    assert!(store.events_between(day(5)..).unwrap().is_empty());
+}
+
+#[test]
+fn decisions_name_the_rule() {
+    let (planner, clock) = setup_test("decisions_name_the_rule", 1, 18);
+
+    clock.next_break();
+    let (reminders, decisions) = planner.reminder_explained(false).unwrap();
+    assert!(reminders.is_empty());
+    assert_eq!(decisions.len(), 1);
+    assert_eq!(decisions[0].rule, Rule::FirstBreak);
+    assert_eq!(decisions[0].relative_future_breaks, Some(12));
+    clock.break_ends();
+
+    clock.next_break();
+    let (_, decisions) = planner.reminder_explained(true).unwrap();
+    assert_eq!(decisions[0].rule, Rule::NotYet);
+    assert_eq!(decisions[0].spacing, Some(6.0));
+    clock.break_ends();
+}
+
+#[test]
+fn status_counts_done() {
+    let (planner, clock) = setup_test("status_counts_done", 2, 14);
+
+    clock.next_break();
+    assert!(!planner.reminder(false).unwrap().is_empty());
+    clock.break_ends();
+
+    let status = planner.status().unwrap();
+    assert_eq!(status.window, Some(planner.window.clone()));
+    let activity = &status.activities[0];
+    assert_eq!((activity.done, activity.remaining), (1, Some(1)));
+    assert!(*activity.next_reminder.as_ref().unwrap() >= clock.now());
+}
+
+#[test]
+fn status_only_reads() {
+    let (planner, clock) = setup_test("status_only_reads", 2, 14);
+
+    clock.next_break();
+    assert!(!planner.reminder(false).unwrap().is_empty());
+    let until = clock.now() + Duration::from_secs(60);
+    planner.pause(Some(until)).unwrap();
+    clock.break_ends();
+
+    // the next day, the pause is over and the counts are stale
+    clock.set(clock.now().tomorrow().unwrap());
+    let last_check = planner.store.last_check().get().unwrap();
+    let events = planner.store.events_between(..).unwrap().len();
+
+    let status = planner.status().unwrap();
+    assert_eq!(status.pause, None);
+    assert_eq!(status.activities[0].done, 0);
+
+    assert_eq!(planner.store.last_check().get().unwrap(), last_check);
+    assert_eq!(planner.store.events_between(..).unwrap().len(), events);
+    assert_eq!(planner.counts_for("test").unwrap().done, 1);
+}
+
+#[test]
+fn pause_respaces_after_resume() {
+    let (planner, clock) = setup_test("pause_respaces_after_resume", 2, 18);
+    let until = clock.now().with().hour(15).build().unwrap();
+    planner.pause(Some(until)).unwrap();
+
+    // `12:25 break - ... - 14:55 break - 15:25 break - ... - 17:55 break`
+    // `     paused                         15:55 and 17:25 reminders    `
+    let mut shown = Vec::new();
+    for i in 0..12 {
+        clock.next_break();
+        if !planner.reminder(false).unwrap().is_empty() {
+            shown.push(i);
+        }
+        clock.break_ends();
+    }
+    assert_eq!(shown, vec![7, 10]);
+    assert!(planner.pause_state().unwrap().is_none());
+}
+
+#[test]
+fn resume_without_pause_is_an_error() {
+    let (planner, clock) =
+        setup_test("resume_without_pause_is_an_error", 2, 18);
+    assert!(planner.resume().is_err());
+
+    planner
+        .pause(Some(clock.now().with().hour(13).build().unwrap()))
+        .unwrap();
+    clock.advance(Duration::from_secs(2 * 60 * 60));
+    // the pause ran out by itself
+    assert!(planner.resume().is_err());
+
+    planner.pause(None).unwrap();
+    planner.resume().unwrap();
+    assert!(planner.pause_state().unwrap().is_none());
+}
+
+#[test]
+fn long_idle_credits_and_offers() {
+    let (mut planner, clock) =
+        setup_test("long_idle_credits_and_offers", 1, 18);
+    planner.activities[0].idle = IdleAction::Offer;
+    planner.activities.push(Activity {
+        description: "walk".to_owned(),
+        count: 1,
+        needs_confirm: true,
+        window: None,
+        idle: IdleAction::Credit,
+        skip: SkipPolicy::Inherit,
+    });
+
+    clock.next_break();
+    assert!(planner.reminder(false).unwrap().is_empty());
+    clock.break_ends();
+
+    clock.advance(Duration::from_secs(60 * 60));
+    let (reminders, _) = planner.returned_explained(true, false).unwrap();
+    let shown: Vec<_> = reminders.iter().map(|a| &a.description).collect();
+    assert_eq!(shown, vec!["test"]);
+    assert_eq!(planner.counts_for("walk").unwrap().done, 1);
+}
+
+#[test]
+fn skip_policy_per_activity() {
+    let (mut planner, clock) = setup_test("skip_policy_per_activity", 2, 14);
+    planner.activities[0].description = "look away".to_owned();
+    for (description, skip) in [
+        ("stretch", SkipPolicy::Never),
+        ("drink", SkipPolicy::Always),
+    ] {
+        planner.activities.push(Activity {
+            description: description.to_owned(),
+            skip,
+            ..planner.activities[0].clone()
+        });
+    }
+
+    clock.next_break();
+    let (reminders, decisions) = planner.reminder_explained(true).unwrap();
+    let shown: Vec<_> = reminders.iter().map(|a| &a.description).collect();
+    assert_eq!(shown, vec!["stretch"]);
+    let rules: Vec<_> = decisions.iter().map(|d| d.rule).collect();
+    assert_eq!(
+        rules,
+        vec![Rule::SkippedForCondition, Rule::Due, Rule::SkippedByPolicy]
+    );
+    clock.break_ends();
+
+    // without a skip condition only the always policy holds back
+    clock.next_break();
+    let (reminders, _) = planner.reminder_explained(false).unwrap();
+    let shown: Vec<_> = reminders.iter().map(|a| &a.description).collect();
+    assert_eq!(shown, vec!["look away"]);
+    clock.break_ends();
+
+    // held back activities are shown once there are no breaks to spare
+    let mut drink_shown = 0;
+    for _ in 0..2 {
+        clock.next_break();
+        let reminders = planner.reminder(true).unwrap();
+        drink_shown += reminders
+            .iter()
+            .filter(|a| a.description == "drink")
+            .count();
+        clock.break_ends();
+    }
+    assert_eq!(drink_shown, 2);
+}
+
+#[test]
+fn meetings_hold_back_and_take_time() {
+    let (mut planner, clock) =
+        setup_test("meetings_hold_back_and_take_time", 1, 14);
+    // the whole first hour of the window is taken by a meeting
+    let start = clock.now();
+    let end = start.checked_add(Duration::from_secs(60 * 60)).unwrap();
+    planner.calendar = Calendar::from(vec![CalendarEvent {
+        summary: "planning".to_owned(),
+        start,
+        end,
+    }]);
+
+    clock.next_break();
+    let (reminders, decisions) = planner.reminder_explained(false).unwrap();
+    assert!(reminders.is_empty());
+    assert_eq!(decisions[0].rule, Rule::SkippedForMeeting);
+    // four breaks fit in the window, two of them during the meeting
+    assert_eq!(decisions[0].relative_future_breaks, Some(2));
+    clock.break_ends();
+
+    // after the meeting it is still shown before the window closes
+    let mut shown = 0;
+    for _ in 0..3 {
+        clock.next_break();
+        shown += planner.reminder(false).unwrap().len();
+        clock.break_ends();
     }
+    assert_eq!(shown, 1);
+}
+
+#[test]
+fn trigger_is_not_a_break() {
+    let (planner, clock) = setup_test("trigger_is_not_a_break", 2, 14);
+
+    clock.next_break();
+    let (reminders, _) = planner.triggered_explained(false).unwrap();
+    assert!(!reminders.is_empty());
+    assert_eq!(planner.breaks().unwrap(), 0);
+    // what the user was shown is counted, it needs no confirmation
+    let counts = planner.counts_for("test").unwrap();
+    assert_eq!((counts.shown, counts.done), (1, 1));
+
+    // and not shown again at the break right after
+    assert!(planner.reminder(false).unwrap().is_empty());
+    assert_eq!(planner.breaks().unwrap(), 1);
+}
+
+#[test]
+fn done_only_counts_outstanding() {
+    let (planner, clock) = setup_test("done_only_counts_outstanding", 2, 14);
+    let activity = planner.activities[0].clone();
+    assert!(planner.is_outstanding(&activity).unwrap());
+
+    clock.next_break();
+    planner.record(EventKind::BreakStarted).unwrap();
+    // shown and counted as done as it needs no confirmation
+    assert!(!planner.reminder(false).unwrap().is_empty());
+    assert!(!planner.is_outstanding(&activity).unwrap());
+    clock.break_ends();
+
+    clock.next_break();
+    planner.record(EventKind::BreakStarted).unwrap();
+    assert!(planner.is_outstanding(&activity).unwrap());
+    planner.mark_completed(&activity.description).unwrap();
+    // both repetitions are done
+    assert!(!planner.is_outstanding(&activity).unwrap());
 }
@@ -0,0 +1,181 @@
+use std::time::Duration;
+
+use break_filler::config::Config;
+use break_filler::record::{Recorded, SourceMessage};
+use break_filler::schedule::Schedule;
+use break_filler::simulate;
+use break_filler::{Activity, IdleAction, SkipPolicy, Store};
+use jiff::civil;
+use jiff::tz::TimeZone;
+use tempfile::tempdir;
+
+#[test]
+fn prints_timeline() {
+    let dir = tempdir().unwrap();
+    let store = Store::new(dir.path().join("db")).unwrap();
+    let config = Config {
+        activities: vec![Activity {
+            description: "drink tea".to_owned(),
+            count: 2,
+            needs_confirm: true,
+            window: None,
+            idle: IdleAction::Ignore,
+            skip: SkipPolicy::Inherit,
+        }],
+        window: "12:00..14:00".parse().unwrap(),
+        load: 1.0,
+        skip_when_visible: Vec::new(),
+        skip_when: None,
+        calendars: Vec::new(),
+        schedule: Schedule::default(),
+        explain: false,
+    };
+
+    let breaks = simulate::day(
+        config,
+        store,
+        civil::date(2026, 10, 19),
+        Duration::from_secs(25 * 60),
+        Duration::from_secs(5 * 60),
+    )
+    .unwrap();
+
+    let timeline: Vec<_> = breaks.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        timeline,
+        vec![
+            "12:25 break #1: drink tea",
+            "12:55 break #2",
+            "13:25 break #3: drink tea",
+            "13:55 break #4",
+        ]
+    );
+}
+
+#[test]
+fn replays_recording() {
+    let dir = tempdir().unwrap();
+    let store = Store::new(dir.path().join("db")).unwrap();
+    let config = Config {
+        activities: vec![Activity {
+            description: "drink tea".to_owned(),
+            count: 2,
+            needs_confirm: true,
+            window: None,
+            idle: IdleAction::Ignore,
+            skip: SkipPolicy::Inherit,
+        }],
+        window: "12:00..14:00".parse().unwrap(),
+        load: 1.0,
+        skip_when_visible: Vec::new(),
+        skip_when: None,
+        calendars: Vec::new(),
+        schedule: Schedule::default(),
+        explain: false,
+    };
+
+    let at = |hour, minute| {
+        civil::date(2026, 10, 19)
+            .at(hour, minute, 0, 0)
+            .to_zoned(TimeZone::system())
+            .unwrap()
+    };
+    let mut recording = vec![Recorded {
+        at: at(12, 0),
+        message: SourceMessage::ParameterChange {
+            break_duration: Duration::from_secs(5 * 60),
+            work_duration: Duration::from_secs(25 * 60),
+        },
+    }];
+    for (hour, minute) in [(12, 25), (12, 55), (13, 25)] {
+        recording.push(Recorded {
+            at: at(hour, minute),
+            message: SourceMessage::BreakStarted,
+        });
+        recording.push(Recorded {
+            at: at(hour, minute) + Duration::from_secs(5 * 60),
+            message: SourceMessage::BreakEnded,
+        });
+    }
+    recording.push(Recorded {
+        at: at(13, 40),
+        message: SourceMessage::LongReset,
+    });
+
+    let replayed = simulate::replay(config, store, &recording).unwrap();
+    let timeline: Vec<_> = replayed.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        timeline,
+        vec![
+            "2026-10-19 12:25 break: drink tea",
+            "2026-10-19 12:55 break",
+            "2026-10-19 13:25 break: drink tea",
+            "2026-10-19 13:40 returned from long break",
+        ]
+    );
+}
+
+#[test]
+fn replay_ignores_return_while_window_open() {
+    let dir = tempdir().unwrap();
+    let store = Store::new(dir.path().join("db")).unwrap();
+    let config = Config {
+        activities: vec![Activity {
+            description: "stretch".to_owned(),
+            count: 2,
+            needs_confirm: false,
+            window: None,
+            idle: IdleAction::Ignore,
+            skip: SkipPolicy::Inherit,
+        }],
+        window: "12:00..14:00".parse().unwrap(),
+        load: 1.0,
+        skip_when_visible: Vec::new(),
+        skip_when: None,
+        calendars: Vec::new(),
+        schedule: Schedule::default(),
+        explain: false,
+    };
+
+    let at = |hour, minute| {
+        civil::date(2026, 10, 19)
+            .at(hour, minute, 0, 0)
+            .to_zoned(TimeZone::system())
+            .unwrap()
+    };
+    let recording = vec![
+        Recorded {
+            at: at(12, 0),
+            message: SourceMessage::ParameterChange {
+                break_duration: Duration::from_secs(5 * 60),
+                work_duration: Duration::from_secs(25 * 60),
+            },
+        },
+        Recorded {
+            at: at(12, 25),
+            message: SourceMessage::BreakStarted,
+        },
+        Recorded {
+            at: at(12, 27),
+            message: SourceMessage::Reset,
+        },
+        Recorded {
+            at: at(12, 30),
+            message: SourceMessage::BreakEnded,
+        },
+        Recorded {
+            at: at(12, 32),
+            message: SourceMessage::Reset,
+        },
+    ];
+
+    let replayed = simulate::replay(config, store, &recording).unwrap();
+    let timeline: Vec<_> = replayed.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        timeline,
+        vec![
+            "2026-10-19 12:25 break: stretch",
+            "2026-10-19 12:32 returned"
+        ]
+    );
+}
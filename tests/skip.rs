@@ -0,0 +1,224 @@
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::Path;
+
+use break_filler::calendar::{Calendar, CalendarEvent};
+use break_filler::cli::{Cli, Command};
+use break_filler::config::Config;
+use break_filler::skip::{devices, Sensors, SkipCondition};
+use break_filler::window_manager::{VisibleWindow, WindowRule};
+use clap::Parser;
+use color_eyre::eyre::eyre;
+use jiff::civil;
+use jiff::tz::TimeZone;
+use jiff::Zoned;
+use tempfile::tempdir;
+
+#[derive(Default)]
+struct FakeSensors {
+    windows: Vec<&'static str>,
+    media_playing: bool,
+    camera_in_use: bool,
+    /// None simulates a missing D-Bus or procfs
+    microphone_in_use: Option<bool>,
+    meetings: Vec<(i8, i8)>,
+}
+
+fn at(hour: i8) -> Zoned {
+    civil::date(2026, 10, 19)
+        .at(hour, 0, 0, 0)
+        .to_zoned(TimeZone::UTC)
+        .unwrap()
+}
+
+impl Sensors for FakeSensors {
+    fn visible_windows(&mut self) -> color_eyre::Result<Vec<VisibleWindow>> {
+        Ok(self
+            .windows
+            .iter()
+            .map(|title| VisibleWindow {
+                title: title.to_string(),
+                ..VisibleWindow::default()
+            })
+            .collect())
+    }
+
+    fn media_playing(&mut self) -> color_eyre::Result<bool> {
+        Ok(self.media_playing)
+    }
+
+    fn camera_in_use(&mut self) -> color_eyre::Result<bool> {
+        Ok(self.camera_in_use)
+    }
+
+    fn microphone_in_use(&mut self) -> color_eyre::Result<bool> {
+        self.microphone_in_use.ok_or_else(|| eyre!("no microphone"))
+    }
+
+    fn calendar(&mut self, _: &Path) -> color_eyre::Result<Calendar> {
+        let events: Vec<_> = self
+            .meetings
+            .iter()
+            .map(|(start, end)| CalendarEvent {
+                summary: "meeting".to_owned(),
+                start: at(*start),
+                end: at(*end),
+            })
+            .collect();
+        Ok(Calendar::from(events))
+    }
+
+    fn now(&self) -> Zoned {
+        at(10)
+    }
+}
+
+#[test]
+fn conditions_combine() {
+    let in_call = SkipCondition::All(vec![
+        SkipCondition::CameraInUse,
+        SkipCondition::Not(Box::new(SkipCondition::Visible(
+            WindowRule::Title("screen share".to_owned()),
+        ))),
+    ]);
+    let condition = SkipCondition::Any(vec![
+        SkipCondition::MediaPlaying,
+        in_call,
+        SkipCondition::InCalendarEvent("work.ics".into()),
+    ]);
+
+    assert!(!condition.holds(&mut FakeSensors::default()));
+    assert!(condition.holds(&mut FakeSensors {
+        media_playing: true,
+        ..FakeSensors::default()
+    }));
+    assert!(condition.holds(&mut FakeSensors {
+        camera_in_use: true,
+        windows: vec!["terminal"],
+        ..FakeSensors::default()
+    }));
+    assert!(!condition.holds(&mut FakeSensors {
+        camera_in_use: true,
+        windows: vec!["Screen share - meet"],
+        ..FakeSensors::default()
+    }));
+    assert!(condition.holds(&mut FakeSensors {
+        meetings: vec![(9, 11)],
+        ..FakeSensors::default()
+    }));
+    assert!(!condition.holds(&mut FakeSensors {
+        meetings: vec![(8, 10), (11, 12)],
+        ..FakeSensors::default()
+    }));
+}
+
+#[test]
+fn unchecked_condition_does_not_hold() {
+    let mut sensors = FakeSensors::default();
+    assert!(!SkipCondition::MicrophoneInUse.holds(&mut sensors));
+    // negating what is unknown stays unknown
+    let not = SkipCondition::Not(Box::new(SkipCondition::MicrophoneInUse));
+    assert!(!not.holds(&mut sensors));
+    // unless the outcome does not depend on it
+    let any = SkipCondition::Any(vec![
+        SkipCondition::MicrophoneInUse,
+        SkipCondition::Not(Box::new(SkipCondition::CameraInUse)),
+    ]);
+    assert!(any.holds(&mut sensors));
+    let all = SkipCondition::All(vec![
+        SkipCondition::MicrophoneInUse,
+        SkipCondition::CameraInUse,
+    ]);
+    assert!(SkipCondition::Not(Box::new(all)).holds(&mut sensors));
+
+    sensors.microphone_in_use = Some(true);
+    assert!(SkipCondition::MicrophoneInUse.holds(&mut sensors));
+    assert!(!not.holds(&mut sensors));
+}
+
+#[test]
+fn parsed_from_config() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(
+        &path,
+        r#"
+skip-when = { any = [
+    "media-playing",
+    { all = ["camera-in-use", { not = { visible = "screen share" } }] },
+    { in-calendar-event = "~/work.ics" },
+] }
+"#,
+    )
+    .unwrap();
+    let cli = Cli::try_parse_from([
+        "break-filler",
+        "run",
+        "--config",
+        path.to_str().unwrap(),
+    ])
+    .unwrap();
+    let Command::Run(run_args) = cli.command else {
+        unreachable!("we passed the run subcommand")
+    };
+
+    let config = Config::resolve(&run_args.config_args).unwrap();
+    assert_eq!(
+        config.skip_when,
+        Some(SkipCondition::Any(vec![
+            SkipCondition::MediaPlaying,
+            SkipCondition::All(vec![
+                SkipCondition::CameraInUse,
+                SkipCondition::Not(Box::new(SkipCondition::Visible(
+                    WindowRule::Title("screen share".to_owned())
+                ))),
+            ]),
+            SkipCondition::InCalendarEvent("~/work.ics".into()),
+        ]))
+    );
+}
+
+#[test]
+fn devices_in_use_from_proc() {
+    let proc = tempdir().unwrap();
+    let fds = proc.path().join("1234").join("fd");
+    fs::create_dir_all(&fds).unwrap();
+    symlink("/dev/null", fds.join("0")).unwrap();
+    assert!(!devices::video_device_open(proc.path()).unwrap());
+    symlink("/dev/video0", fds.join("7")).unwrap();
+    assert!(devices::video_device_open(proc.path()).unwrap());
+
+    let capture = proc.path().join("asound/card0/pcm0c/sub0");
+    fs::create_dir_all(&capture).unwrap();
+    fs::write(capture.join("status"), "closed\n").unwrap();
+    assert!(!devices::capture_running(proc.path()).unwrap());
+    fs::write(capture.join("status"), "state: RUNNING\nowner_pid   : 42\n")
+        .unwrap();
+    assert!(devices::capture_running(proc.path()).unwrap());
+}
+
+#[test]
+fn pipewire_nodes() {
+    let dump = r#"[
+        { "id": 0, "type": "PipeWire:Interface:Core", "info": {} },
+        {
+            "id": 50,
+            "type": "PipeWire:Interface:Node",
+            "info": {
+                "state": "suspended",
+                "props": { "media.class": "Audio/Source" }
+            }
+        },
+        {
+            "id": 51,
+            "type": "PipeWire:Interface:Node",
+            "info": {
+                "state": "running",
+                "props": { "media.class": "Video/Source" }
+            }
+        }
+    ]"#;
+    assert!(devices::pipewire_running(dump, &["Video/Source"]));
+    assert!(!devices::pipewire_running(dump, &["Audio/Source"]));
+    assert!(!devices::pipewire_running("not json", &["Video/Source"]));
+}
@@ -0,0 +1,90 @@
+use std::io::Cursor;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use break_filler::record::{self, Recorder, SourceMessage};
+use break_filler::script::Script;
+use break_filler::source::BreakSource;
+use break_filler::standalone::{Idle, Standalone};
+use break_filler::time::SystemClock;
+use break_filler::Message;
+use tempfile::tempdir;
+
+#[test]
+fn script_yields_events_in_order() {
+    let mut script = Script::new(Cursor::new(
+        "# a short day\n\
+         parameter-change 25m 5m\n\
+         sleep 1ms\n\
+         break-started\n\
+         \n\
+         break-ended # back to work\n\
+         went-idle\n\
+         reset\n\
+         long-reset\n",
+    ));
+
+    assert!(matches!(
+        script.next(),
+        Some(Message::ParameterChange {
+            work_duration,
+            break_duration,
+        }) if work_duration == Duration::from_secs(25 * 60)
+            && break_duration == Duration::from_secs(5 * 60)
+    ));
+    assert!(matches!(script.next(), Some(Message::BreakStarted)));
+    assert!(matches!(script.next(), Some(Message::BreakEnded)));
+    assert!(matches!(script.next(), Some(Message::WentIdle)));
+    assert!(matches!(script.next(), Some(Message::Reset)));
+    assert!(matches!(script.next(), Some(Message::LongReset)));
+    assert!(script.next().is_none());
+}
+
+#[test]
+fn script_skips_invalid_lines() {
+    let mut script = Script::new(Cursor::new(
+        "break-start\n\
+         parameter-change 25m\n\
+         sleep soon\n\
+         break-started\n",
+    ));
+    assert!(matches!(script.next(), Some(Message::BreakStarted)));
+    assert!(script.next().is_none());
+}
+
+#[test]
+fn recorder_writes_what_it_passes_on() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("recording.jsonl");
+    let script = Script::new(Cursor::new("break-started\nbreak-ended\n"));
+    let file = record::open(&path).unwrap();
+    let mut recorder = Recorder::new(script, file, Arc::new(SystemClock));
+    while recorder.next().is_some() {}
+
+    let messages: Vec<_> = record::read(&path)
+        .unwrap()
+        .into_iter()
+        .map(|recorded| recorded.message)
+        .collect();
+    assert_eq!(
+        messages,
+        vec![SourceMessage::BreakStarted, SourceMessage::BreakEnded]
+    );
+}
+
+#[test]
+fn standalone_ignores_idle_during_break() {
+    let (tx, rx) = mpsc::channel();
+    let short = Duration::from_millis(20);
+    let mut standalone = Standalone::with_idle(short, short, Some(rx));
+    assert!(matches!(
+        standalone.next(),
+        Some(Message::ParameterChange { .. })
+    ));
+    assert!(matches!(standalone.next(), Some(Message::BreakStarted)));
+
+    tx.send(Idle::Started).unwrap();
+    tx.send(Idle::Stopped).unwrap();
+    assert!(matches!(standalone.next(), Some(Message::BreakEnded)));
+    assert!(matches!(standalone.next(), Some(Message::BreakStarted)));
+}
@@ -0,0 +1,95 @@
+use break_filler::history::EventKind;
+use break_filler::stats::summarize;
+
+use common::events;
+
+mod common;
+
+fn shown(activity: &str) -> EventKind {
+    EventKind::Shown {
+        activity: activity.to_owned(),
+    }
+}
+
+#[test]
+fn outcomes_are_told_apart() {
+    let events = events(vec![
+        EventKind::BreakStarted,
+        shown("drink"),
+        EventKind::AutoCompleted {
+            activity: "drink".to_owned(),
+        },
+        shown("stretch"),
+        EventKind::Confirmed {
+            activity: "stretch".to_owned(),
+        },
+        EventKind::BreakEnded,
+        EventKind::BreakStarted,
+        EventKind::BreakEnded,
+        EventKind::BreakStarted,
+        shown("drink"),
+        EventKind::AutoCompleted {
+            activity: "drink".to_owned(),
+        },
+        EventKind::Skipped {
+            activity: "drink".to_owned(),
+            reason: None,
+        },
+        shown("stretch"),
+        EventKind::BreakEnded,
+    ]);
+
+    let stats = summarize(&events, None);
+    assert_eq!(stats.breaks, 3);
+    assert_eq!(stats.breaks_without_reminder, 1);
+
+    let drink = &stats.activities[0];
+    assert_eq!(drink.activity, "drink");
+    assert_eq!((drink.shown, drink.done, drink.skipped), (2, 1, 1));
+    assert_eq!(drink.completion_rate, Some(0.5));
+
+    let stretch = &stats.activities[1];
+    assert_eq!((stretch.shown, stretch.done, stretch.ignored), (2, 1, 1));
+    assert_eq!(stretch.avg_secs_to_confirm, Some(60.0));
+
+    let only_drink = summarize(&events, Some("Drin"));
+    assert_eq!(only_drink.activities.len(), 1);
+    assert_eq!(only_drink.activities[0].activity, "drink");
+}
+
+#[test]
+fn later_confirmation_does_not_change_outcome() {
+    let mut events = events(vec![
+        EventKind::BreakStarted,
+        shown("drink"),
+        EventKind::Skipped {
+            activity: "drink".to_owned(),
+            reason: None,
+        },
+        shown("stretch"),
+        EventKind::BreakEnded,
+        EventKind::Confirmed {
+            activity: "drink".to_owned(),
+        },
+        EventKind::BreakStarted,
+        EventKind::Confirmed {
+            activity: "stretch".to_owned(),
+        },
+    ]);
+    // for example marked done with `break-filler done` the next day
+    let next_day = events[0].at.tomorrow().unwrap();
+    for event in &mut events[5..] {
+        event.at = next_day.clone();
+    }
+
+    let stats = summarize(&events, None);
+    let drink = &stats.activities[0];
+    assert_eq!((drink.shown, drink.done, drink.skipped), (1, 0, 1));
+    assert_eq!(drink.avg_secs_to_confirm, None);
+    // still open, the confirmation counts however late it came
+    let stretch = &stats.activities[1];
+    assert_eq!((stretch.shown, stretch.done, stretch.ignored), (1, 1, 0));
+    assert!(stretch
+        .avg_secs_to_confirm
+        .is_some_and(|secs| secs > 23.0 * 60.0 * 60.0));
+}
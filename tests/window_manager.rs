@@ -0,0 +1,341 @@
+use std::io::{Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use break_filler::window_manager::{
+    Hyprland, I3Ipc, VisibleWindow, WindowManager, Wlr,
+};
+use serde_json::json;
+use tempfile::tempdir;
+
+fn titles(mut windows: Vec<VisibleWindow>) -> Vec<String> {
+    windows.sort_by(|a, b| a.title.cmp(&b.title));
+    windows.into_iter().map(|window| window.title).collect()
+}
+
+/// Answers `connections` requests using `reply` to pick the answer
+fn serve(
+    path: &Path,
+    connections: usize,
+    reply: impl Fn(&mut UnixStream) + Send + 'static,
+) {
+    let listener = UnixListener::bind(path).unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming().take(connections) {
+            reply(&mut stream.unwrap());
+        }
+    });
+}
+
+/// Answers the messages on one connection the way i3 and Sway do
+fn fake_i3(
+    dir: &Path,
+    workspaces: serde_json::Value,
+    tree: serde_json::Value,
+) -> PathBuf {
+    const GET_WORKSPACES: u32 = 1;
+    const GET_TREE: u32 = 4;
+    let path = dir.join("ipc.sock");
+    serve(&path, 1, move |stream| loop {
+        let mut header = [0u8; 14];
+        if stream.read_exact(&mut header).is_err() {
+            return; // client disconnected
+        }
+        assert_eq!(&header[..6], b"i3-ipc");
+        let message_type = u32::from_ne_bytes(header[10..].try_into().unwrap());
+        let payload = match message_type {
+            GET_WORKSPACES => workspaces.to_string(),
+            GET_TREE => tree.to_string(),
+            other => panic!("unexpected message type: {other}"),
+        };
+        let mut reply = b"i3-ipc".to_vec();
+        reply.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+        reply.extend_from_slice(&message_type.to_ne_bytes());
+        reply.extend_from_slice(payload.as_bytes());
+        stream.write_all(&reply).unwrap();
+    });
+    path
+}
+
+fn rect() -> serde_json::Value {
+    json!({ "x": 0, "y": 0, "width": 800, "height": 600 })
+}
+
+/// A node of the tree with the fields every node has, `fields` are
+/// added to or replace them
+fn node(
+    kind: &str,
+    name: &str,
+    fields: serde_json::Value,
+) -> serde_json::Value {
+    let mut node = json!({
+        "id": 1,
+        "name": name,
+        "type": kind,
+        "border": "none",
+        "current_border_width": 0,
+        "layout": "splith",
+        "rect": rect(),
+        "window_rect": rect(),
+        "deco_rect": rect(),
+        "geometry": rect(),
+        "urgent": false,
+        "focused": false,
+        "focus": [],
+        "sticky": false,
+        "nodes": [],
+        "floating_nodes": [],
+    });
+    let serde_json::Value::Object(fields) = fields else {
+        panic!("fields must be an object");
+    };
+    node.as_object_mut().unwrap().extend(fields);
+    node
+}
+
+fn workspace(name: &str, visible: bool) -> serde_json::Value {
+    json!({
+        "id": 1,
+        "num": name.parse::<i32>().unwrap_or(-1),
+        "name": name,
+        "layout": "splith",
+        "visible": visible,
+        "focused": visible,
+        "urgent": false,
+        "orientation": "horizontal",
+        "rect": rect(),
+        "output": "HDMI-1",
+        "focus": [],
+    })
+}
+
+#[test]
+fn i3_windows_on_visible_workspaces() {
+    let dir = tempdir().unwrap();
+    let workspaces = json!([workspace("1", true), workspace("2", false)]);
+    let firefox = node(
+        "con",
+        "youtube - firefox",
+        json!({
+            "window": 1,
+            "window_properties": { "class": "firefox" },
+            "focused": true,
+            "fullscreen_mode": 1,
+        }),
+    );
+    let calculator = node("con", "calculator", json!({ "window": 2 }));
+    let floating = node("floating_con", "", json!({ "nodes": [calculator] }));
+    let editor = node("con", "editor", json!({ "window": 3 }));
+    let visible = node(
+        "workspace",
+        "1",
+        json!({ "nodes": [firefox], "floating_nodes": [floating] }),
+    );
+    let hidden = node("workspace", "2", json!({ "nodes": [editor] }));
+    let output =
+        node("output", "HDMI-1", json!({ "nodes": [visible, hidden] }));
+    let tree = node("root", "root", json!({ "nodes": [output] }));
+
+    let socket = fake_i3(dir.path(), workspaces, tree);
+    let windows = I3Ipc::i3_at(&socket).unwrap().visible_windows().unwrap();
+    let firefox = windows
+        .iter()
+        .find(|window| window.title == "youtube - firefox")
+        .unwrap();
+    assert_eq!(
+        firefox,
+        &VisibleWindow {
+            title: "youtube - firefox".to_owned(),
+            app_id: Some("firefox".to_owned()),
+            focused: true,
+            fullscreen: true,
+            workspace: Some("1".to_owned()),
+        }
+    );
+    assert_eq!(titles(windows), vec!["calculator", "youtube - firefox"]);
+}
+
+#[test]
+fn sway_windows_marked_visible() {
+    let dir = tempdir().unwrap();
+    let workspaces = json!([workspace("1", true)]);
+    let terminal = node(
+        "con",
+        "terminal",
+        json!({ "app_id": "foot", "visible": true }),
+    );
+    let hidden = node(
+        "con",
+        "hidden tab",
+        json!({ "app_id": "foot", "visible": false }),
+    );
+    let visible =
+        node("workspace", "1", json!({ "nodes": [terminal, hidden] }));
+    let tree = node("root", "root", json!({ "nodes": [visible] }));
+
+    let socket = fake_i3(dir.path(), workspaces, tree);
+    let windows = I3Ipc::sway_at(&socket).unwrap().visible_windows().unwrap();
+    assert_eq!(windows[0].app_id.as_deref(), Some("foot"));
+    assert_eq!(titles(windows), vec!["terminal"]);
+}
+
+#[test]
+fn hyprland_windows_on_active_workspaces() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join(".socket.sock");
+    serve(&path, 2, |stream| {
+        let mut command = [0u8; 64];
+        let len = stream.read(&mut command).unwrap();
+        let reply = match &command[..len] {
+            b"j/monitors" => json!([{
+                "activeWorkspace": { "id": 1, "name": "1" },
+                "specialWorkspace": { "id": 0, "name": "" },
+            }]),
+            b"j/clients" => json!([
+                {
+                    "mapped": true,
+                    "hidden": false,
+                    "workspace": { "id": 1, "name": "1" },
+                    "title": "mpv",
+                    "class": "mpv",
+                    "fullscreen": 2,
+                    "focusHistoryID": 0,
+                },
+                {
+                    "mapped": true,
+                    "hidden": false,
+                    "workspace": { "id": 2, "name": "2" },
+                    "title": "mail",
+                    "class": "thunderbird",
+                    "fullscreen": false,
+                },
+                {
+                    "mapped": true,
+                    "hidden": true,
+                    "workspace": { "id": 1, "name": "1" },
+                    "title": "grouped away",
+                    "class": "foot",
+                    "fullscreen": 0,
+                    "focusHistoryID": 1,
+                },
+            ]),
+            other => panic!("unexpected command: {other:?}"),
+        };
+        stream.write_all(reply.to_string().as_bytes()).unwrap();
+    });
+
+    let windows = Hyprland::new(path).visible_windows().unwrap();
+    assert_eq!(
+        windows,
+        vec![VisibleWindow {
+            title: "mpv".to_owned(),
+            app_id: Some("mpv".to_owned()),
+            focused: true,
+            fullscreen: true,
+            workspace: Some("1".to_owned()),
+        }]
+    );
+}
+
+/// Just enough of a wayland compositor to announce a
+/// zwlr_foreign_toplevel_manager_v1 and its toplevels
+fn fake_compositor(mut stream: UnixStream, windows: &[(&str, &[u32])]) {
+    const DISPLAY: u32 = 1;
+    let mut registry = None;
+    loop {
+        let mut header = [0u8; 8];
+        if stream.read_exact(&mut header).is_err() {
+            return; // client disconnected
+        }
+        let object = u32::from_ne_bytes(header[..4].try_into().unwrap());
+        let word = u32::from_ne_bytes(header[4..].try_into().unwrap());
+        let (opcode, size) = (word & 0xffff, word >> 16);
+        let mut body = vec![0u8; size as usize - 8];
+        stream.read_exact(&mut body).unwrap();
+        let arg = |idx: usize| {
+            u32::from_ne_bytes(body[idx * 4..idx * 4 + 4].try_into().unwrap())
+        };
+
+        match (object, opcode) {
+            // get_registry
+            (DISPLAY, 1) => {
+                registry = Some(arg(0));
+                let mut args = uint(1);
+                args.extend(string("zwlr_foreign_toplevel_manager_v1"));
+                args.extend(uint(3));
+                send(&mut stream, arg(0), 0, &args);
+            }
+            // sync
+            (DISPLAY, 0) => {
+                send(&mut stream, arg(0), 0, &uint(0));
+                send(&mut stream, DISPLAY, 1, &uint(arg(0)));
+            }
+            // bind, the last argument is the id of the new object
+            (object, 0) if Some(object) == registry => {
+                let manager = arg(body.len() / 4 - 1);
+                for (idx, (title, states)) in windows.iter().enumerate() {
+                    let handle = 0xff00_0000 + idx as u32;
+                    send(&mut stream, manager, 0, &uint(handle));
+                    send(&mut stream, handle, 0, &string(title));
+                    send(&mut stream, handle, 1, &string(title));
+                    let state: Vec<u8> =
+                        states.iter().flat_map(|s| s.to_ne_bytes()).collect();
+                    send(&mut stream, handle, 4, &array(&state));
+                    send(&mut stream, handle, 5, &[]);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn send(stream: &mut UnixStream, object: u32, opcode: u32, args: &[u8]) {
+    let size = 8 + args.len() as u32;
+    let mut message = uint(object);
+    message.extend(uint(size << 16 | opcode));
+    message.extend_from_slice(args);
+    stream.write_all(&message).unwrap();
+}
+
+fn uint(value: u32) -> Vec<u8> {
+    value.to_ne_bytes().to_vec()
+}
+
+fn string(value: &str) -> Vec<u8> {
+    let mut bytes = value.as_bytes().to_vec();
+    bytes.push(0);
+    array(&bytes)
+}
+
+fn array(value: &[u8]) -> Vec<u8> {
+    let mut res = uint(value.len() as u32);
+    res.extend_from_slice(value);
+    res.resize(res.len().next_multiple_of(4), 0);
+    res
+}
+
+#[test]
+fn wlr_windows_not_minimized() {
+    const ACTIVATED: u32 = 2;
+    const MINIMIZED: u32 = 1;
+    let (client, server) = UnixStream::pair().unwrap();
+    thread::spawn(move || {
+        fake_compositor(
+            server,
+            &[("firefox", &[ACTIVATED]), ("chat", &[MINIMIZED])],
+        )
+    });
+
+    let windows = Wlr::from_socket(client).unwrap().visible_windows().unwrap();
+    assert_eq!(
+        windows,
+        vec![VisibleWindow {
+            title: "firefox".to_owned(),
+            app_id: Some("firefox".to_owned()),
+            focused: true,
+            fullscreen: false,
+            workspace: None,
+        }]
+    );
+}